@@ -0,0 +1,369 @@
+use std::{
+	collections::BTreeMap,
+	pin::Pin,
+	sync::{Arc, Mutex},
+	task::{Context as TaskContext, Poll, Waker},
+};
+
+use futures_core::stream::{FusedStream, Stream};
+use futures_sink::Sink;
+
+use crate::{
+	aio::{Aio, AioResult},
+	ctx::Context,
+	error::{Error, Result},
+	future::Target,
+	message::Message,
+	socket::Socket,
+};
+
+/// An adapter that exposes a socket or context as a `Stream` and `Sink` of
+/// messages.
+///
+/// Receives are driven by a pool of internal [`Aio`] objects, each of which
+/// keeps a receive operation posted on the socket. Once a received message has
+/// been taken out of the stream, the `Aio` that produced it is immediately
+/// posted again, which keeps the number of buffered messages bounded by the
+/// size of the pool. No receives are posted until the stream is first polled,
+/// so an adapter that is only used as a `Sink` will never consume messages.
+/// Messages are yielded in the order that the receive operations were posted,
+/// which is the order in which NNG hands them out, even if the `Aio` objects
+/// happen to complete in a different order.
+///
+/// The stream ends once the underlying socket or context is closed. Any other
+/// error is yielded as the last item of the stream, as it would most likely be
+/// reported again by every following receive.
+///
+/// Sends are performed one at a time on a separate internal `Aio`. Dropping
+/// the adapter cancels all outstanding operations.
+///
+/// This type is only available when the `futures` feature is enabled.
+///
+/// ## Example
+///
+/// ```
+/// use futures::{executor::block_on, SinkExt, StreamExt};
+/// use nng::*;
+///
+/// let server = Socket::new(Protocol::Pair0)?;
+/// server.listen("inproc://nng/adapter/example")?;
+///
+/// let client = Socket::new(Protocol::Pair0)?;
+/// client.dial("inproc://nng/adapter/example")?;
+///
+/// let mut incoming = MessageStream::new(&server, 4)?;
+/// let mut outgoing = MessageStream::new(&client, 1)?;
+///
+/// block_on(async {
+///     for i in 0..8u8 {
+///         outgoing.send(Message::from(&[i][..])).await?;
+///         let msg = incoming.next().await.unwrap()?;
+///         assert_eq!(msg[0], i);
+///     }
+///
+///     Ok::<(), Error>(())
+/// })?;
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`Aio`]: struct.Aio.html
+#[derive(Debug)]
+pub struct MessageStream
+{
+	/// The socket or context on which operations are performed.
+	target: Target,
+
+	/// The pool of `Aio` objects used for receiving messages.
+	recv_aios: Vec<Aio>,
+
+	/// The `Aio` object used for sending messages.
+	send_aio: Aio,
+
+	/// Whether or not the receive operations have been posted.
+	started: bool,
+
+	/// Whether or not a send operation is currently outstanding.
+	sending: bool,
+
+	/// The state shared with the `Aio` callbacks.
+	shared: Arc<Mutex<Shared>>,
+}
+impl MessageStream
+{
+	/// Creates a new adapter for the socket that keeps `depth` receive
+	/// operations posted at a time.
+	///
+	/// A `depth` of zero is treated as one.
+	///
+	/// # Errors
+	///
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn new(socket: &Socket, depth: usize) -> Result<Self>
+	{
+		MessageStream::with_target(Target::Socket(socket.clone()), depth)
+	}
+
+	/// Creates a new adapter for the context.
+	///
+	/// Contexts only allow a single receive operation to be outstanding at a
+	/// time, so this adapter will only ever have one receive posted.
+	///
+	/// # Errors
+	///
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn with_context(ctx: &Context) -> Result<Self>
+	{
+		MessageStream::with_target(Target::Context(ctx.clone()), 1)
+	}
+
+	/// Creates the adapter and all of its `Aio` objects.
+	fn with_target(target: Target, depth: usize) -> Result<Self>
+	{
+		let depth = depth.max(1);
+		let shared = Arc::new(Mutex::new(Shared { running: vec![0; depth], ..Shared::default() }));
+
+		let recv_aios = (0..depth)
+			.map(|i| {
+				let shared = Arc::clone(&shared);
+				Aio::new(move |_, res| {
+					if let AioResult::Recv(r) = res {
+						Shared::complete_recv(&shared, i, r);
+					}
+				})
+			})
+			.collect::<Result<_>>()?;
+
+		let send_shared = Arc::clone(&shared);
+		let send_aio = Aio::new(move |_, res| {
+			if let AioResult::Send(r) = res {
+				Shared::complete_send(&send_shared, r.map_err(|(_, e)| e));
+			}
+		})?;
+
+		Ok(MessageStream { target, recv_aios, send_aio, started: false, sending: false, shared })
+	}
+
+	/// Posts a receive operation on the specified `Aio`.
+	///
+	/// Failing to start the operation is reported as if the operation itself
+	/// had failed.
+	fn post(&self, index: usize)
+	{
+		self.shared.lock().unwrap().start_recv(index);
+
+		let aio = &self.recv_aios[index];
+		let res = match &self.target {
+			Target::Socket(s) => aio.recv_socket(s),
+			Target::Context(c) => aio.recv_ctx(c),
+		};
+
+		if let Err(e) = res {
+			Shared::complete_recv(&self.shared, index, Err(e));
+		}
+	}
+
+	/// Checks on the outstanding send operation, if there is one.
+	fn poll_send(&mut self, cx: &mut TaskContext) -> Poll<Result<()>>
+	{
+		if !self.sending {
+			return Poll::Ready(Ok(()));
+		}
+
+		let mut shared = self.shared.lock().unwrap();
+		if let Some(res) = shared.sent.take() {
+			self.sending = false;
+			return Poll::Ready(res);
+		}
+
+		shared.send_waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+}
+
+impl Stream for MessageStream
+{
+	type Item = Result<Message>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Option<Self::Item>>
+	{
+		let this = self.get_mut();
+		if !this.started {
+			this.started = true;
+			(0..this.recv_aios.len()).for_each(|i| this.post(i));
+		}
+
+		let next = this.shared.lock().unwrap().next(cx.waker());
+		let (index, res) = match next {
+			Poll::Ready(Some(item)) => item,
+			Poll::Ready(None) => return Poll::Ready(None),
+			Poll::Pending => return Poll::Pending,
+		};
+
+		// The result has been handed off, so the `Aio` that produced it can be put
+		// back to work. After an error, the stream ends instead and the receives
+		// that are still outstanding are no longer needed.
+		if res.is_ok() {
+			this.post(index);
+		}
+		else {
+			this.recv_aios.iter().for_each(Aio::cancel);
+		}
+
+		Poll::Ready(Some(res))
+	}
+}
+
+impl FusedStream for MessageStream
+{
+	fn is_terminated(&self) -> bool { self.shared.lock().unwrap().ended }
+}
+
+impl Sink<Message> for MessageStream
+{
+	type Error = Error;
+
+	fn poll_ready(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Result<()>>
+	{
+		self.get_mut().poll_send(cx)
+	}
+
+	fn start_send(self: Pin<&mut Self>, item: Message) -> Result<()>
+	{
+		let this = self.get_mut();
+		let res = match &this.target {
+			Target::Socket(s) => this.send_aio.send_socket(s, item),
+			Target::Context(c) => this.send_aio.send_ctx(c, item),
+		};
+
+		res.map_err(|(_, e)| e)?;
+		this.sending = true;
+		Ok(())
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Result<()>>
+	{
+		self.get_mut().poll_send(cx)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Result<()>>
+	{
+		self.get_mut().poll_send(cx)
+	}
+}
+
+impl Drop for MessageStream
+{
+	fn drop(&mut self)
+	{
+		// As with the futures, waiting on each `Aio` makes sure that none of the
+		// callbacks are still holding on to a handle by the time ours are dropped.
+		for aio in self.recv_aios.iter().chain(Some(&self.send_aio)) {
+			aio.cancel();
+			aio.wait();
+		}
+	}
+}
+
+/// The state shared between the adapter and the `Aio` callbacks.
+#[derive(Debug, Default)]
+struct Shared
+{
+	/// Completed receive operations and the index of the `Aio` that ran them,
+	/// keyed by the sequence number of the receive.
+	received: BTreeMap<u64, (usize, Result<Message>)>,
+
+	/// The sequence number of the receive that each `Aio` is running.
+	running: Vec<u64>,
+
+	/// The sequence number of the next receive to be posted.
+	next_posted: u64,
+
+	/// The sequence number of the next receive to be yielded.
+	next_yielded: u64,
+
+	/// The result of the last send operation, if it has completed.
+	sent: Option<Result<()>>,
+
+	/// Whether or not the stream has ended.
+	ended: bool,
+
+	/// The waker of the task waiting on a received message.
+	recv_waker: Option<Waker>,
+
+	/// The waker of the task waiting on a send to complete.
+	send_waker: Option<Waker>,
+}
+impl Shared
+{
+	/// Takes the next received message, registering the waker if there isn't
+	/// one yet.
+	fn next(&mut self, waker: &Waker) -> Poll<Option<(usize, Result<Message>)>>
+	{
+		if self.ended {
+			return Poll::Ready(None);
+		}
+
+		// Later receives may have completed already, but they have to wait their turn.
+		let Some(item) = self.received.remove(&self.next_yielded)
+		else {
+			self.recv_waker = Some(waker.clone());
+			return Poll::Pending;
+		};
+
+		self.next_yielded += 1;
+		match item {
+			(_, Err(Error::Closed | Error::Canceled)) => {
+				self.ended = true;
+				Poll::Ready(None)
+			},
+			item => {
+				self.ended = item.1.is_err();
+				Poll::Ready(Some(item))
+			},
+		}
+	}
+
+	/// Assigns the next sequence number to the receive that the `Aio` is about
+	/// to run.
+	fn start_recv(&mut self, index: usize)
+	{
+		self.running[index] = self.next_posted;
+		self.next_posted += 1;
+	}
+
+	/// Stores the result of a receive operation and wakes the waiting task.
+	fn complete_recv(shared: &Mutex<Shared>, index: usize, res: Result<Message>)
+	{
+		let waker = {
+			let mut shared = shared.lock().unwrap();
+			let seq = shared.running[index];
+			shared.received.insert(seq, (index, res));
+			shared.recv_waker.take()
+		};
+
+		if let Some(w) = waker {
+			w.wake();
+		}
+	}
+
+	/// Stores the result of a send operation and wakes the waiting task.
+	fn complete_send(shared: &Mutex<Shared>, res: Result<()>)
+	{
+		let waker = {
+			let mut shared = shared.lock().unwrap();
+			shared.sent = Some(res);
+			shared.send_waker.take()
+		};
+
+		if let Some(w) = waker {
+			w.wake();
+		}
+	}
+}
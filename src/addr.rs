@@ -39,11 +39,11 @@ impl fmt::Display for SocketAddr
 	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
 	{
 		match self {
-			SocketAddr::InProc(s) => write!(f, "inproc://{}", s),
+			SocketAddr::InProc(s) => write!(f, "inproc://{s}"),
 			SocketAddr::Ipc(s) => write!(f, "ipc://{}", s.to_string_lossy()),
-			SocketAddr::Inet(s) => write!(f, "tcp://{}", s),
-			SocketAddr::Inet6(s) => write!(f, "tcp://{}", s),
-			SocketAddr::ZeroTier(s) => write!(f, "zt://{}", s),
+			SocketAddr::Inet(s) => write!(f, "tcp://{s}"),
+			SocketAddr::Inet6(s) => write!(f, "tcp://{s}"),
+			SocketAddr::ZeroTier(s) => write!(f, "zt://{s}"),
 			SocketAddr::Unspecified => write!(f, "unspecified"),
 		}
 	}
@@ -64,17 +64,19 @@ impl From<nng_sys::nng_sockaddr> for SocketAddr
 				},
 				Some(nng_sys::nng_sockaddr_family::NNG_AF_INET) => {
 					let v4_addr = u32::from_be(addr.s_in.sa_addr).into();
-					SocketAddr::Inet(SocketAddrV4::new(v4_addr, addr.s_in.sa_port))
+					SocketAddr::Inet(SocketAddrV4::new(v4_addr, u16::from_be(addr.s_in.sa_port)))
 				},
 				Some(nng_sys::nng_sockaddr_family::NNG_AF_INET6) => {
 					let v6_addr = addr.s_in6.sa_addr.into();
-					let port = addr.s_in6.sa_port;
+					let port = u16::from_be(addr.s_in6.sa_port);
 					SocketAddr::Inet6(SocketAddrV6::new(v6_addr, port, 0, 0))
 				},
 				Some(nng_sys::nng_sockaddr_family::NNG_AF_ZT) => {
 					SocketAddr::ZeroTier(SocketAddrZt::new(&addr.s_zt))
 				},
-				Some(nng_sys::nng_sockaddr_family::NNG_AF_UNSPEC) | None => SocketAddr::Unspecified,
+				// Families that this crate does not know about, such as the abstract
+				// sockets of newer versions of NNG, are treated as unspecified.
+				_ => SocketAddr::Unspecified,
 			}
 		}
 	}
@@ -0,0 +1,155 @@
+use crate::{
+	error::{Error, Result},
+	message::Header,
+};
+
+/// The bit that marks the final entry of a backtrace.
+const END_BIT: u32 = 0x8000_0000;
+
+/// The routing information carried in the header of _req_, _rep_, _surveyor_
+/// and _respondent_ messages.
+///
+/// As a request travels from a requester to a replier, every device that it
+/// passes through records the ID of the pipe that it arrived on. The result is
+/// a stack of pipe IDs, the most recent first, followed by the ID of the
+/// request itself which has its high bit set. The reply retraces these steps by
+/// having each device remove the first pipe ID and send the reply on that pipe.
+///
+/// Sockets in "raw" mode expose this stack in the message [`Header`], which
+/// this type can be parsed from and written to. The same format is used by the
+/// survey protocols, where the final entry is the survey ID.
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+///
+/// let server = RawSocket::new(Protocol::Rep0)?.socket;
+/// server.listen("inproc://nng/backtrace/example")?;
+///
+/// let client = Socket::new(Protocol::Req0)?;
+/// client.dial("inproc://nng/backtrace/example")?;
+/// client.send(&b"ping"[..]).map_err(Error::from)?;
+///
+/// // The raw socket recorded the pipe that the request arrived on.
+/// let msg = server.recv()?;
+/// let backtrace = Backtrace::parse(msg.as_header())?;
+/// assert_eq!(backtrace.hop_count(), 1);
+///
+/// // Writing the backtrace back to the header routes the reply.
+/// let mut reply = Message::from(&b"pong"[..]);
+/// backtrace.write(reply.as_mut_header());
+/// server.send(reply).map_err(Error::from)?;
+///
+/// assert_eq!(&client.recv()?[..], b"pong");
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`Header`]: struct.Header.html
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Backtrace
+{
+	/// The pipe IDs that the message passed through, the most recent first.
+	hops: Vec<u32>,
+
+	/// The request or survey ID, without the end bit.
+	request_id: u32,
+}
+impl Backtrace
+{
+	/// Creates a backtrace with no hops for the given request or survey ID.
+	///
+	/// Only the lower 31 bits of the ID are used.
+	pub fn new(request_id: u32) -> Self
+	{
+		Backtrace { hops: Vec::new(), request_id: request_id & !END_BIT }
+	}
+
+	/// Parses the backtrace contained in the header.
+	///
+	/// # Errors
+	///
+	/// * [`Protocol`]: The header does not contain a well-formed backtrace.
+	///
+	///
+	/// [`Protocol`]: enum.Error.html#variant.Protocol
+	pub fn parse(header: &Header) -> Result<Self>
+	{
+		let bytes = header.as_slice();
+		if !bytes.len().is_multiple_of(4) {
+			return Err(Error::Protocol);
+		}
+
+		let mut hops = Vec::with_capacity(bytes.len() / 4);
+		for (i, chunk) in bytes.chunks(4).enumerate() {
+			let mut word = [0; 4];
+			word.copy_from_slice(chunk);
+
+			let word = u32::from_be_bytes(word);
+			if word & END_BIT == 0 {
+				hops.push(word);
+			}
+			else if (i + 1) * 4 == bytes.len() {
+				return Ok(Backtrace { hops, request_id: word & !END_BIT });
+			}
+			else {
+				// There is data after the end of the backtrace.
+				return Err(Error::Protocol);
+			}
+		}
+
+		// The header ended without the request ID.
+		Err(Error::Protocol)
+	}
+
+	/// Replaces the contents of the header with this backtrace.
+	pub fn write(&self, header: &mut Header)
+	{
+		header.clear();
+		for &hop in &self.hops {
+			header.push_back_u32(hop);
+		}
+		header.push_back_u32(self.request_id | END_BIT);
+	}
+
+	/// Returns the request or survey ID, without the high bit.
+	pub const fn request_id(&self) -> u32 { self.request_id }
+
+	/// Returns the IDs of the pipes the message passed through, the most
+	/// recent first.
+	pub fn hops(&self) -> &[u32] { &self.hops }
+
+	/// Returns the number of hops that the message has taken.
+	pub fn hop_count(&self) -> usize { self.hops.len() }
+
+	/// Returns true if the message has taken more hops than allowed by the
+	/// given [`MaxTtl`] value.
+	///
+	/// _Rep_ and _respondent_ sockets silently drop any message that fails this
+	/// check as it arrives, with the pipe it arrived on already counted as a
+	/// hop. A message forwarded by a device arrives at the next socket with one
+	/// more hop than the device saw.
+	///
+	///
+	/// [`MaxTtl`]: options/enum.MaxTtl.html
+	pub fn exceeds_ttl(&self, max_ttl: u8) -> bool { self.hops.len() > usize::from(max_ttl) }
+
+	/// Records a new hop, making it the most recent.
+	///
+	/// # Panics
+	///
+	/// Panics if the high bit of the pipe ID is set, as NNG pipe IDs only use
+	/// the lower 31 bits.
+	pub fn push_hop(&mut self, pipe_id: u32)
+	{
+		assert!(pipe_id & END_BIT == 0, "Pipe IDs cannot have the high bit set");
+		self.hops.insert(0, pipe_id);
+	}
+
+	/// Removes and returns the most recent hop.
+	pub fn pop_hop(&mut self) -> Option<u32>
+	{
+		if self.hops.is_empty() { None } else { Some(self.hops.remove(0)) }
+	}
+}
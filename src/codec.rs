@@ -0,0 +1,220 @@
+//! Serialization of values into and out of messages.
+//!
+//! A [`Codec`] describes how a value is turned into the bytes of a message
+//! body and back again. Values are serialized directly into the message
+//! through its `io::Write` implementation, so there is no intermediate buffer
+//! to copy out of.
+//!
+//! This module is only available when the `serde` feature is enabled. The
+//! individual codecs are enabled by their own features:
+//!
+//! * `codec-json`: [`Json`], using `serde_json`.
+//! * `codec-cbor`: [`Cbor`], using `ciborium`.
+//! * `codec-bincode`: [`Bincode`], using `bincode`.
+//! * `codec-msgpack`: [`MessagePack`], using `rmp-serde`.
+//!
+//! Other formats can be supported by implementing the `Codec` trait.
+//!
+//! [`Codec`]: trait.Codec.html
+//! [`Json`]: struct.Json.html
+//! [`Cbor`]: struct.Cbor.html
+//! [`Bincode`]: struct.Bincode.html
+//! [`MessagePack`]: struct.MessagePack.html
+use std::{error, fmt, io};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::Error;
+
+/// Boxed error returned by the underlying serialization library.
+type BoxError = Box<dyn error::Error + Send + Sync + 'static>;
+
+/// A format that values can be serialized into and deserialized from.
+pub trait Codec
+{
+	/// Serializes the value into the writer.
+	///
+	/// # Errors
+	///
+	/// * [`CodecError::Encode`]: The value could not be serialized.
+	///
+	///
+	/// [`CodecError::Encode`]: enum.CodecError.html#variant.Encode
+	fn encode<T, W>(&self, value: &T, writer: W) -> Result<(), CodecError>
+	where
+		T: Serialize + ?Sized,
+		W: io::Write;
+
+	/// Deserializes a value from the bytes.
+	///
+	/// # Errors
+	///
+	/// * [`CodecError::Decode`]: The bytes are not a valid value.
+	///
+	///
+	/// [`CodecError::Decode`]: enum.CodecError.html#variant.Decode
+	fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, CodecError>;
+}
+
+/// Errors returned when sending or receiving serialized values.
+#[derive(Debug)]
+pub enum CodecError
+{
+	/// The NNG operation failed.
+	Nng(Error),
+
+	/// The value could not be serialized.
+	Encode(BoxError),
+
+	/// The message could not be deserialized.
+	Decode(BoxError),
+}
+
+impl From<Error> for CodecError
+{
+	fn from(e: Error) -> CodecError { CodecError::Nng(e) }
+}
+
+impl fmt::Display for CodecError
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self {
+			CodecError::Nng(e) => write!(f, "{e}"),
+			CodecError::Encode(e) => write!(f, "Failed to serialize value: {e}"),
+			CodecError::Decode(e) => write!(f, "Failed to deserialize message: {e}"),
+		}
+	}
+}
+
+impl error::Error for CodecError
+{
+	fn source(&self) -> Option<&(dyn error::Error + 'static)>
+	{
+		match self {
+			CodecError::Nng(e) => Some(e),
+			CodecError::Encode(e) | CodecError::Decode(e) => Some(&**e),
+		}
+	}
+}
+
+/// The JSON format, using `serde_json`.
+///
+/// This type is only available when the `codec-json` feature is enabled.
+///
+/// ## Example
+///
+/// ```
+/// use nng::{codec::Json, *};
+/// use serde::{Deserialize, Serialize};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct Point { x: i32, y: i32 }
+///
+/// let server = Socket::new(Protocol::Pair0)?;
+/// server.listen("inproc://nng/codec/example")?;
+///
+/// let client = Socket::new(Protocol::Pair0)?;
+/// client.dial("inproc://nng/codec/example")?;
+///
+/// client.send_typed(&Point { x: 1, y: 2 }, Json)?;
+/// let point: Point = server.recv_typed(Json)?;
+/// assert_eq!(point, Point { x: 1, y: 2 });
+/// # Ok::<(), nng::codec::CodecError>(())
+/// ```
+#[cfg(feature = "codec-json")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Json;
+
+#[cfg(feature = "codec-json")]
+impl Codec for Json
+{
+	fn encode<T, W>(&self, value: &T, writer: W) -> Result<(), CodecError>
+	where
+		T: Serialize + ?Sized,
+		W: io::Write,
+	{
+		serde_json::to_writer(writer, value).map_err(|e| CodecError::Encode(Box::new(e)))
+	}
+
+	fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, CodecError>
+	{
+		serde_json::from_slice(bytes).map_err(|e| CodecError::Decode(Box::new(e)))
+	}
+}
+
+/// The CBOR format, using `ciborium`.
+///
+/// This type is only available when the `codec-cbor` feature is enabled.
+#[cfg(feature = "codec-cbor")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Cbor;
+
+#[cfg(feature = "codec-cbor")]
+impl Codec for Cbor
+{
+	fn encode<T, W>(&self, value: &T, writer: W) -> Result<(), CodecError>
+	where
+		T: Serialize + ?Sized,
+		W: io::Write,
+	{
+		ciborium::ser::into_writer(value, writer).map_err(|e| CodecError::Encode(Box::new(e)))
+	}
+
+	fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, CodecError>
+	{
+		ciborium::de::from_reader(bytes).map_err(|e| CodecError::Decode(Box::new(e)))
+	}
+}
+
+/// The bincode format, using `bincode` with its default configuration.
+///
+/// This type is only available when the `codec-bincode` feature is enabled.
+#[cfg(feature = "codec-bincode")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Bincode;
+
+#[cfg(feature = "codec-bincode")]
+impl Codec for Bincode
+{
+	fn encode<T, W>(&self, value: &T, writer: W) -> Result<(), CodecError>
+	where
+		T: Serialize + ?Sized,
+		W: io::Write,
+	{
+		bincode::serialize_into(writer, value).map_err(|e| CodecError::Encode(e))
+	}
+
+	fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, CodecError>
+	{
+		bincode::deserialize(bytes).map_err(|e| CodecError::Decode(e))
+	}
+}
+
+/// The `MessagePack` format, using `rmp-serde`.
+///
+/// Structures are serialized as maps with their field names, which keeps the
+/// messages readable by implementations in other languages.
+///
+/// This type is only available when the `codec-msgpack` feature is enabled.
+#[cfg(feature = "codec-msgpack")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct MessagePack;
+
+#[cfg(feature = "codec-msgpack")]
+impl Codec for MessagePack
+{
+	fn encode<T, W>(&self, value: &T, mut writer: W) -> Result<(), CodecError>
+	where
+		T: Serialize + ?Sized,
+		W: io::Write,
+	{
+		rmp_serde::encode::write_named(&mut writer, value)
+			.map_err(|e| CodecError::Encode(Box::new(e)))
+	}
+
+	fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, CodecError>
+	{
+		rmp_serde::from_slice(bytes).map_err(|e| CodecError::Decode(Box::new(e)))
+	}
+}
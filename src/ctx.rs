@@ -7,6 +7,7 @@ use std::{
 use crate::{
 	aio::Aio,
 	error::{Result, SendResult},
+	future::{RecvFuture, SendFuture, Target},
 	message::Message,
 	socket::Socket,
 };
@@ -80,6 +81,22 @@ impl Context
 	/// [`IncorrectState`]: enum.Error.html#variant.IncorrectState
 	pub fn recv(&self, aio: &Aio) -> Result<()> { aio.recv_ctx(self) }
 
+	/// Returns a future that resolves to the next message received on the context.
+	///
+	/// The receive operation does not start until the future is first polled.
+	/// Dropping the future before it completes cancels the receive.
+	pub fn recv_fut(&self) -> RecvFuture { RecvFuture::new(Target::Context(self.clone())) }
+
+	/// Returns a future that resolves once the message has been sent on the context.
+	///
+	/// The send operation does not start until the future is first polled.
+	/// Dropping the future before it completes cancels the send. If the message
+	/// could not be sent, it is returned as a part of the error.
+	pub fn send_fut<M: Into<Message>>(&self, msg: M) -> SendFuture
+	{
+		SendFuture::new(Target::Context(self.clone()), msg.into())
+	}
+
 	/// Closes the context.
 	///
 	/// Messages that have been submitted for sending may be flushed or
@@ -146,6 +163,7 @@ expose_options!{
 	GETOPT_BOOL = nng_sys::nng_ctx_get_bool;
 	GETOPT_INT = nng_sys::nng_ctx_get_int;
 	GETOPT_MS = nng_sys::nng_ctx_get_ms;
+	GETOPT_PTR = nng_sys::nng_ctx_get_ptr;
 	GETOPT_SIZE = nng_sys::nng_ctx_get_size;
 	GETOPT_SOCKADDR = nng_sys::nng_ctx_get_addr;
 	GETOPT_STRING = nng_sys::nng_ctx_get_string;
@@ -1,8 +1,15 @@
-use std::num::NonZeroU32;
+use std::{
+	fmt,
+	num::NonZeroU32,
+	sync::{Arc, Condvar, Mutex},
+};
 
 use crate::{
+	aio::{Aio, AioResult},
 	error::{Error, Result},
-	socket::RawSocket,
+	message::Message,
+	protocol::Protocol,
+	socket::{RawSocket, Socket},
 };
 
 /// Forwards messages between two sockets.
@@ -23,6 +30,8 @@ use crate::{
 ///
 /// This function does not return unless one of the sockets encounters an
 /// error or is closed. For more information see the [NNG documentation][1].
+/// A forwarder that can be stopped without closing its sockets can be created
+/// with [`Device::forwarder`].
 ///
 /// # Errors
 ///
@@ -35,6 +44,7 @@ use crate::{
 ///
 /// [1]: https://nanomsg.github.io/nng/man/v1.2.2/nng_device.3
 /// [`Closed`]: enum.Error.html#variant.Closed
+/// [`Device::forwarder`]: struct.Device.html#method.forwarder
 /// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
 /// [`MaxTtl`]: options/enum.MaxTtl.html
 /// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
@@ -62,7 +72,9 @@ pub fn forwarder(s1: RawSocket, s2: RawSocket) -> Result<()>
 /// back to the sender.
 ///
 /// This function does not return unless the socket encounters an error or is
-/// closed. For more information, see the [NNG documentation][1].
+/// closed. For more information, see the [NNG documentation][1]. A reflector
+/// that can be stopped without closing its socket can be created with
+/// [`Device::reflector`].
 ///
 /// # Errors
 ///
@@ -75,6 +87,7 @@ pub fn forwarder(s1: RawSocket, s2: RawSocket) -> Result<()>
 ///
 /// [1]: https://nanomsg.github.io/nng/man/v1.2.2/nng_device.3
 /// [`Closed`]: enum.Error.html#variant.Closed
+/// [`Device::reflector`]: struct.Device.html#method.reflector
 /// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
 /// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
 pub fn reflector(s1: RawSocket) -> Result<()>
@@ -92,3 +105,509 @@ pub fn reflector(s1: RawSocket) -> Result<()>
 		unreachable!("nng_device returned with no errror");
 	}
 }
+
+/// A running forwarder or reflector device.
+///
+/// Unlike [`forwarder`] and [`reflector`], which block the calling thread until
+/// one of the sockets is closed, a `Device` forwards messages in the
+/// background using [`Aio`] objects. It can be stopped at any time without
+/// closing its sockets, which means that a new device can later be started on
+/// the same sockets.
+///
+/// Dropping the device stops it and waits for it to finish.
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+///
+/// let front = RawSocket::new(Protocol::Pair1)?;
+/// front.socket.listen("inproc://nng/device/front")?;
+/// let back = RawSocket::new(Protocol::Pair1)?;
+/// back.socket.listen("inproc://nng/device/back")?;
+///
+/// let device = Device::forwarder(front, back)?;
+///
+/// let client = Socket::new(Protocol::Pair1)?;
+/// client.dial("inproc://nng/device/front")?;
+/// let server = Socket::new(Protocol::Pair1)?;
+/// server.dial("inproc://nng/device/back")?;
+///
+/// client.send(&b"Ferris"[..]).map_err(Error::from)?;
+/// assert_eq!(&server.recv()?[..], b"Ferris");
+///
+/// device.stop();
+/// device.join()?;
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`Aio`]: struct.Aio.html
+/// [`forwarder`]: fn.forwarder.html
+/// [`reflector`]: fn.reflector.html
+#[derive(Debug)]
+pub struct Device
+{
+	/// The state shared with the forwarding paths.
+	shared: Arc<Shared>,
+}
+impl Device
+{
+	/// Starts forwarding messages between two sockets.
+	///
+	/// The sockets must have protocols that are compatible with each other, as
+	/// described by [`forwarder`]. Messages flow in both directions if both
+	/// protocols are able to receive.
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: Sockets are not compatible.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	/// Errors that occur on the sockets once the device is running are
+	/// reported by [`Device::join`].
+	///
+	///
+	/// [`Device::join`]: struct.Device.html#method.join
+	/// [`forwarder`]: fn.forwarder.html
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn forwarder(s1: RawSocket, s2: RawSocket) -> Result<Self>
+	{
+		Device::start(forwarder_paths(s1, s2)?, None)
+	}
+
+	/// Starts forwarding messages between two sockets, passing each one
+	/// through the hook.
+	///
+	/// The hook is called with every message that the device receives, along
+	/// with the direction that it is travelling in. The message is forwarded
+	/// if the hook returns it, either as it was or modified, and dropped if the
+	/// hook returns `None`. Messages are passed to the hook unchanged from how
+	/// they were received, which means that the [`Header`] contains the routing
+	/// information of the raw socket (see [`Backtrace`]) and should be
+	/// preserved for the message to reach its destination.
+	///
+	/// The hook is never called concurrently, but it is called from threads
+	/// managed by NNG and so should not block for long. If the hook panics,
+	/// the process is aborted.
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: Sockets are not compatible.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	/// Errors that occur on the sockets once the device is running are
+	/// reported by [`Device::join`].
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use nng::*;
+	///
+	/// let front = RawSocket::new(Protocol::Pair1)?;
+	/// front.socket.listen("inproc://nng/device/hook/front")?;
+	/// let back = RawSocket::new(Protocol::Pair1)?;
+	/// back.socket.listen("inproc://nng/device/hook/back")?;
+	///
+	/// // Drop empty messages and stamp the rest with the direction they took.
+	/// let device = Device::forwarder_with(front, back, |dir, mut msg| {
+	///     if msg.is_empty() {
+	///         return None;
+	///     }
+	///
+	///     msg.push_back(if dir == Direction::Forward { b">" } else { b"<" });
+	///     Some(msg)
+	/// })?;
+	///
+	/// let client = Socket::new(Protocol::Pair1)?;
+	/// client.dial("inproc://nng/device/hook/front")?;
+	/// let server = Socket::new(Protocol::Pair1)?;
+	/// server.dial("inproc://nng/device/hook/back")?;
+	///
+	/// client.send(Message::new()).map_err(Error::from)?;
+	/// client.send(&b"ping"[..]).map_err(Error::from)?;
+	/// assert_eq!(&server.recv()?[..], b"ping>");
+	///
+	/// server.send(&b"pong"[..]).map_err(Error::from)?;
+	/// assert_eq!(&client.recv()?[..], b"pong<");
+	///
+	/// device.stop();
+	/// device.join()?;
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`Backtrace`]: struct.Backtrace.html
+	/// [`Device::join`]: struct.Device.html#method.join
+	/// [`Header`]: struct.Header.html
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn forwarder_with<F>(s1: RawSocket, s2: RawSocket, hook: F) -> Result<Self>
+	where
+		F: FnMut(Direction, Message) -> Option<Message> + Send + 'static,
+	{
+		Device::start(forwarder_paths(s1, s2)?, Some(Box::new(hook)))
+	}
+
+	/// Starts reflecting the messages received on the socket back at itself.
+	///
+	/// The socket must have a protocol that can peer with itself, as described
+	/// by [`reflector`].
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: The socket is not capable of sending messages to
+	///   itself.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	/// Errors that occur on the socket once the device is running are reported
+	/// by [`Device::join`].
+	///
+	///
+	/// [`Device::join`]: struct.Device.html#method.join
+	/// [`reflector`]: fn.reflector.html
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn reflector(s1: RawSocket) -> Result<Self> { Device::start(reflector_paths(s1)?, None) }
+
+	/// Starts reflecting the messages received on the socket back at itself,
+	/// passing each one through the hook.
+	///
+	/// The hook behaves as it does for [`Device::forwarder_with`] and is always
+	/// called with [`Direction::Forward`].
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: The socket is not capable of sending messages to
+	///   itself.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	/// Errors that occur on the socket once the device is running are reported
+	/// by [`Device::join`].
+	///
+	///
+	/// [`Device::forwarder_with`]: struct.Device.html#method.forwarder_with
+	/// [`Device::join`]: struct.Device.html#method.join
+	/// [`Direction::Forward`]: enum.Direction.html#variant.Forward
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn reflector_with<F>(s1: RawSocket, hook: F) -> Result<Self>
+	where
+		F: FnMut(Direction, Message) -> Option<Message> + Send + 'static,
+	{
+		Device::start(reflector_paths(s1)?, Some(Box::new(hook)))
+	}
+
+	/// Asks the device to stop forwarding messages.
+	///
+	/// This function returns immediately. Use [`Device::join`] to wait for the
+	/// device to finish. Any message that has been received but not yet
+	/// forwarded when the device stops is dropped. The sockets are not closed.
+	///
+	///
+	/// [`Device::join`]: struct.Device.html#method.join
+	#[allow(clippy::missing_panics_doc)]
+	pub fn stop(&self) { self.shared.stop() }
+
+	/// Waits for the device to finish.
+	///
+	/// # Errors
+	///
+	/// Returns `Ok(())` if the device finished because it was asked to stop.
+	/// Otherwise, returns the error that caused one of the sockets to stop
+	/// forwarding, such as [`Closed`] if one of the sockets was closed.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	#[allow(clippy::missing_panics_doc)]
+	pub fn join(self) -> Result<()>
+	{
+		let mut state = self.shared.state.lock().unwrap();
+		while state.running > 0 {
+			state = self.shared.finished.wait(state).unwrap();
+		}
+
+		state.error.map_or(Ok(()), Err)
+	}
+
+	/// Creates an `Aio` for each path and starts receiving on them.
+	fn start(paths: Vec<Path>, hook: Option<Box<HookFn>>) -> Result<Self>
+	{
+		let shared = Arc::new(Shared {
+			state:    Mutex::new(DeviceState {
+				aios:     Vec::with_capacity(paths.len()),
+				running:  0,
+				stopping: false,
+				error:    None,
+			}),
+			finished: Condvar::new(),
+			hook:     hook.map(Mutex::new),
+		});
+		let device = Device { shared: Arc::clone(&shared) };
+
+		let mut starts = Vec::with_capacity(paths.len());
+		for path in paths {
+			let path_shared = Arc::clone(&shared);
+			let src = path.src.clone();
+			let aio = Aio::new(move |aio, res| path_shared.callback(&aio, &path, res))?;
+
+			// Devices run until stopped, no matter what the socket timeouts say.
+			aio.set_timeout(None)?;
+			shared.state.lock().unwrap().aios.push(aio.clone());
+			starts.push((aio, src));
+		}
+
+		for (aio, src) in starts {
+			shared.state.lock().unwrap().running += 1;
+			if let Err(e) = aio.recv_socket(&src) {
+				shared.finish(e);
+				break;
+			}
+			shared.cancel_if_stopping(&aio);
+		}
+
+		Ok(device)
+	}
+}
+
+impl Drop for Device
+{
+	fn drop(&mut self)
+	{
+		self.shared.stop();
+
+		let mut state = self.shared.state.lock().unwrap();
+		while state.running > 0 {
+			state = self.shared.finished.wait(state).unwrap();
+		}
+		let aios: Vec<_> = state.aios.drain(..).collect();
+		drop(state);
+
+		// The callbacks of the `Aio` objects hold on to the shared state, so the objects need to
+		// be released here to avoid leaking them. Waiting first makes sure that the last handle
+		// isn't dropped by a callback that is still returning.
+		for aio in &aios {
+			aio.wait();
+		}
+	}
+}
+
+/// The direction in which a message is travelling through a [`Device`].
+///
+///
+/// [`Device`]: struct.Device.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Direction
+{
+	/// The message was received on the first socket and is being sent on the
+	/// second. Reflectors only forward messages in this direction.
+	Forward,
+
+	/// The message was received on the second socket and is being sent on the
+	/// first.
+	Backward,
+}
+
+/// The hook that messages are passed through by a `Device`.
+type HookFn = dyn FnMut(Direction, Message) -> Option<Message> + Send + 'static;
+
+/// A direction in which a `Device` forwards messages.
+#[derive(Debug)]
+struct Path
+{
+	/// The socket messages are received on.
+	src: Socket,
+
+	/// The socket messages are sent on.
+	dst: Socket,
+
+	/// The direction messages travel in relative to the user's sockets.
+	direction: Direction,
+}
+
+/// Builds the paths of a forwarder, making sure that the sockets are compatible.
+fn forwarder_paths(s1: RawSocket, s2: RawSocket) -> Result<Vec<Path>>
+{
+	let (s1, s2) = (s1.socket, s2.socket);
+	let (p1, p2) = (s1.protocol(), s2.protocol());
+	if peer(p1) != p2 || peer(p2) != p1 {
+		return Err(Error::InvalidInput);
+	}
+
+	let mut paths = Vec::with_capacity(2);
+	if can_recv(p1) {
+		paths.push(Path {
+			src:       s1.clone(),
+			dst:       s2.clone(),
+			direction: Direction::Forward,
+		});
+	}
+	if can_recv(p2) {
+		paths.push(Path { src: s2, dst: s1, direction: Direction::Backward });
+	}
+
+	Ok(paths)
+}
+
+/// Builds the path of a reflector, making sure that the socket can peer with itself.
+fn reflector_paths(s1: RawSocket) -> Result<Vec<Path>>
+{
+	let s1 = s1.socket;
+	if peer(s1.protocol()) != s1.protocol() {
+		return Err(Error::InvalidInput);
+	}
+
+	Ok(vec![Path { src: s1.clone(), dst: s1, direction: Direction::Forward }])
+}
+
+/// The state shared between a `Device` and its forwarding paths.
+struct Shared
+{
+	/// The mutable state of the device.
+	state: Mutex<DeviceState>,
+
+	/// Signaled when the last path has finished.
+	finished: Condvar,
+
+	/// The hook that every received message is passed through, if any.
+	hook: Option<Mutex<Box<HookFn>>>,
+}
+impl Shared
+{
+	/// Moves a path along to its next operation or finishes it.
+	fn callback(&self, aio: &Aio, path: &Path, res: AioResult)
+	{
+		// The hook runs without the state lock so that a slow hook doesn't hold up `stop`.
+		let res = match (res, &self.hook) {
+			(AioResult::Recv(Ok(msg)), Some(hook)) => {
+				match (hook.lock().unwrap())(path.direction, msg) {
+					Some(msg) => AioResult::Recv(Ok(msg)),
+
+					// Dropping the message means moving straight on to the next receive, the same
+					// as if the message had been sent.
+					None => AioResult::Send(Ok(())),
+				}
+			},
+			(res, _) => res,
+		};
+
+		let stopping = self.state.lock().unwrap().stopping;
+		let err = match res {
+			AioResult::Recv(Ok(_)) | AioResult::Send(Ok(())) if stopping => Error::Canceled,
+			AioResult::Recv(Ok(msg)) => match aio.send_socket(&path.dst, msg) {
+				Ok(()) => return self.cancel_if_stopping(aio),
+				Err((_, e)) => e,
+			},
+			AioResult::Send(Ok(())) => match aio.recv_socket(&path.src) {
+				Ok(()) => return self.cancel_if_stopping(aio),
+				Err(e) => e,
+			},
+			AioResult::Recv(Err(e)) | AioResult::Send(Err((_, e))) => e,
+			AioResult::Sleep(_) => unreachable!("device paths never sleep"),
+		};
+
+		self.finish(err);
+	}
+
+	/// Marks the device as stopping and cancels all running operations.
+	fn stop(&self)
+	{
+		// Operations can complete synchronously and run their callback on this thread, so the
+		// lock can't be held while cancelling them.
+		let aios = {
+			let mut state = self.state.lock().unwrap();
+			state.stopping = true;
+			state.aios.clone()
+		};
+
+		for aio in aios {
+			aio.cancel();
+		}
+	}
+
+	/// Cancels the operation that was just started if the device started stopping meanwhile.
+	///
+	/// The lock isn't held while starting operations, as they can complete synchronously and run
+	/// the callback on the same thread. Instead, either `stop` sets the flag before this check
+	/// and the path cancels itself, or it sets it after and its cancellation sees the operation.
+	fn cancel_if_stopping(&self, aio: &Aio)
+	{
+		if self.state.lock().unwrap().stopping {
+			aio.cancel();
+		}
+	}
+
+	/// Finishes a path, stopping the whole device if it failed on its own.
+	fn finish(&self, err: Error)
+	{
+		let failed = {
+			let mut state = self.state.lock().unwrap();
+			let failed = !state.stopping;
+			if failed {
+				state.error = Some(err);
+			}
+			failed
+		};
+
+		if failed {
+			self.stop();
+		}
+
+		let mut state = self.state.lock().unwrap();
+		state.running -= 1;
+		if state.running == 0 {
+			self.finished.notify_all();
+		}
+	}
+}
+
+impl fmt::Debug for Shared
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		f.debug_struct("Shared")
+			.field("state", &self.state)
+			.field("finished", &self.finished)
+			.field("hook", &self.hook.is_some())
+			.finish()
+	}
+}
+
+/// The mutable state of a device.
+#[derive(Debug)]
+struct DeviceState
+{
+	/// The `Aio` objects running each path.
+	aios: Vec<Aio>,
+
+	/// The number of paths that are still running.
+	running: usize,
+
+	/// Whether or not the device has been asked to stop.
+	stopping: bool,
+
+	/// The error that stopped the device, if it was not asked to stop.
+	error: Option<Error>,
+}
+
+/// Returns the protocol that is the peer of the given one.
+fn peer(protocol: Protocol) -> Protocol
+{
+	match protocol {
+		Protocol::Bus0 => Protocol::Bus0,
+		Protocol::Pair0 => Protocol::Pair0,
+		Protocol::Pair1 => Protocol::Pair1,
+		Protocol::Pub0 => Protocol::Sub0,
+		Protocol::Pull0 => Protocol::Push0,
+		Protocol::Push0 => Protocol::Pull0,
+		Protocol::Rep0 => Protocol::Req0,
+		Protocol::Req0 => Protocol::Rep0,
+		Protocol::Respondent0 => Protocol::Surveyor0,
+		Protocol::Sub0 => Protocol::Pub0,
+		Protocol::Surveyor0 => Protocol::Respondent0,
+	}
+}
+
+/// Returns true if sockets of the protocol are able to receive messages.
+fn can_recv(protocol: Protocol) -> bool { !matches!(protocol, Protocol::Pub0 | Protocol::Push0) }
@@ -8,6 +8,7 @@ use std::{
 use crate::{
 	error::{Error, Result},
 	socket::Socket,
+	stats::{self, Stats},
 };
 
 /// An active outgoing connection.
@@ -111,6 +112,27 @@ impl Dialer
 		);
 	}
 
+	/// Takes a snapshot of the statistics of this dialer.
+	///
+	/// The root of the snapshot is the scope of the dialer. See [`Stats`] for
+	/// more information.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The dialer is not open.
+	/// * [`NotSupported`]: NNG was built without support for statistics.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`Stats`]: struct.Stats.html
+	pub fn stats(self) -> Result<Stats>
+	{
+		stats::scoped("dialer", unsafe { nng_sys::nng_dialer_id(self.handle) })
+	}
+
 	/// Create a new Dialer handle from an NNG handle.
 	///
 	/// This function will panic if the handle is not valid.
@@ -171,6 +193,7 @@ expose_options!{
 	GETOPT_BOOL = nng_sys::nng_dialer_get_bool;
 	GETOPT_INT = nng_sys::nng_dialer_get_int;
 	GETOPT_MS = nng_sys::nng_dialer_get_ms;
+	GETOPT_PTR = nng_sys::nng_dialer_get_ptr;
 	GETOPT_SIZE = nng_sys::nng_dialer_get_size;
 	GETOPT_SOCKADDR = nng_sys::nng_dialer_get_addr;
 	GETOPT_STRING = nng_sys::nng_dialer_get_string;
@@ -314,6 +337,7 @@ expose_options!{
 	GETOPT_BOOL = nng_sys::nng_dialer_get_bool;
 	GETOPT_INT = nng_sys::nng_dialer_get_int;
 	GETOPT_MS = nng_sys::nng_dialer_get_ms;
+	GETOPT_PTR = nng_sys::nng_dialer_get_ptr;
 	GETOPT_SIZE = nng_sys::nng_dialer_get_size;
 	GETOPT_SOCKADDR = nng_sys::nng_dialer_get_addr;
 	GETOPT_STRING = nng_sys::nng_dialer_get_string;
@@ -342,6 +366,7 @@ expose_options!{
 	         transport::tcp::KeepAlive,
 	         transport::tls::CaFile,
 	         transport::tls::CertKeyFile,
+	         transport::tls::Config,
 	         transport::websocket::RequestHeaders,
 	         transport::websocket::Protocol];
 }
@@ -0,0 +1,327 @@
+use std::{
+	future::Future,
+	pin::Pin,
+	sync::{Arc, Mutex},
+	task::{Context as TaskContext, Poll, Wake, Waker},
+	thread::{self, Thread},
+	time::Duration,
+};
+
+use crate::{
+	aio::{Aio, AioResult},
+	ctx::Context,
+	error::{Error, Result, SendResult},
+	message::Message,
+	socket::Socket,
+};
+
+/// A future that resolves to a message received on a socket or context.
+///
+/// The receive operation is started the first time the future is polled and it
+/// is driven by an internal [`Aio`], so no particular runtime is required.
+/// Dropping the future before it completes will cancel the operation.
+///
+/// This is created by [`Socket::recv_fut`] and [`Context::recv_fut`].
+///
+/// ## Example
+///
+/// ```
+/// use futures::executor::block_on;
+/// use nng::*;
+///
+/// let server = Socket::new(Protocol::Pair0)?;
+/// server.listen("inproc://nng/future/example")?;
+///
+/// let client = Socket::new(Protocol::Pair0)?;
+/// client.dial("inproc://nng/future/example")?;
+///
+/// let reply = server.recv_fut();
+/// block_on(client.send_fut(&b"Ferris"[..])).map_err(Error::from)?;
+///
+/// let msg = block_on(reply)?;
+/// assert_eq!(&msg[..], b"Ferris");
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`Aio`]: struct.Aio.html
+/// [`Context::recv_fut`]: struct.Context.html#method.recv_fut
+/// [`Socket::recv_fut`]: struct.Socket.html#method.recv_fut
+#[derive(Debug)]
+#[must_use = "the receive does not start until the future is polled"]
+pub struct RecvFuture
+{
+	/// The operation backing this future.
+	op: Operation,
+}
+impl RecvFuture
+{
+	/// Creates a new future that receives on the given target.
+	pub(crate) fn new(target: Target) -> Self
+	{
+		RecvFuture { op: Operation::new(target, Start::Recv) }
+	}
+
+	/// Sets the timeout of the receive operation.
+	pub(crate) fn with_timeout(mut self, dur: Option<Duration>) -> Self
+	{
+		self.op.timeout = dur;
+		self
+	}
+}
+
+impl Future for RecvFuture
+{
+	type Output = Result<Message>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Self::Output>
+	{
+		self.get_mut().op.poll(cx).map(|res| {
+			if let AioResult::Recv(r) = res {
+				r
+			}
+			else {
+				unreachable!("receive operation completed with a non-receive result")
+			}
+		})
+	}
+}
+
+/// A future that resolves once a message has been sent on a socket or context.
+///
+/// The send operation is started the first time the future is polled and it is
+/// driven by an internal [`Aio`], so no particular runtime is required. If the
+/// message cannot be sent, it is returned to the caller as a part of the error.
+/// Dropping the future before it completes will cancel the operation.
+///
+/// This is created by [`Socket::send_fut`] and [`Context::send_fut`].
+///
+///
+/// [`Aio`]: struct.Aio.html
+/// [`Context::send_fut`]: struct.Context.html#method.send_fut
+/// [`Socket::send_fut`]: struct.Socket.html#method.send_fut
+#[derive(Debug)]
+#[must_use = "the send does not start until the future is polled"]
+pub struct SendFuture
+{
+	/// The operation backing this future.
+	op: Operation,
+}
+impl SendFuture
+{
+	/// Creates a new future that sends the message on the given target.
+	pub(crate) fn new(target: Target, msg: Message) -> Self
+	{
+		SendFuture { op: Operation::new(target, Start::Send(msg)) }
+	}
+
+	/// Sets the timeout of the send operation.
+	pub(crate) fn with_timeout(mut self, dur: Option<Duration>) -> Self
+	{
+		self.op.timeout = dur;
+		self
+	}
+}
+
+impl Future for SendFuture
+{
+	type Output = SendResult<()>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Self::Output>
+	{
+		self.get_mut().op.poll(cx).map(|res| {
+			if let AioResult::Send(r) = res {
+				r
+			}
+			else {
+				unreachable!("send operation completed with a non-send result")
+			}
+		})
+	}
+}
+
+/// The object on which an operation is performed.
+#[derive(Clone, Debug)]
+#[allow(clippy::redundant_pub_crate)] // Makes it clear that this is not part of the public API
+pub(crate) enum Target
+{
+	/// Operate directly on the socket.
+	Socket(Socket),
+
+	/// Operate on a single context of a socket.
+	Context(Context),
+}
+
+/// An operation that has not yet been started.
+#[derive(Debug)]
+enum Start
+{
+	/// Receive a message.
+	Recv,
+
+	/// Send the message.
+	Send(Message),
+}
+impl Start
+{
+	/// Converts a failure to start the operation into the matching result.
+	fn fail(self, err: Error) -> AioResult
+	{
+		match self {
+			Start::Recv => AioResult::Recv(Err(err)),
+			Start::Send(msg) => AioResult::Send(Err((msg, err))),
+		}
+	}
+}
+
+/// An `Aio` operation that reports its completion to a waker.
+#[derive(Debug)]
+struct Operation
+{
+	/// The socket or context on which the operation is performed.
+	///
+	/// This is kept around for the lifetime of the operation as dropping the
+	/// last handle to a context closes it.
+	target: Target,
+
+	/// The operation to start on the first poll.
+	start: Option<Start>,
+
+	/// The timeout to apply to the operation.
+	timeout: Option<Duration>,
+
+	/// The AIO object running the operation, once it has been started.
+	aio: Option<Aio>,
+
+	/// The completion state shared with the AIO callback.
+	shared: Arc<Mutex<Slot>>,
+}
+impl Operation
+{
+	/// Creates a new operation that has not yet been started.
+	fn new(target: Target, start: Start) -> Self
+	{
+		Operation {
+			target,
+			start: Some(start),
+			timeout: None,
+			aio: None,
+			shared: Arc::new(Mutex::new(Slot::default())),
+		}
+	}
+
+	/// Starts the operation if needed and checks whether it has finished.
+	fn poll(&mut self, cx: &mut TaskContext) -> Poll<AioResult>
+	{
+		if let Some(start) = self.start.take() {
+			if let Err(res) = self.begin(start) {
+				return Poll::Ready(res);
+			}
+		}
+
+		let mut slot = self.shared.lock().unwrap();
+		if let Some(res) = slot.result.take() {
+			return Poll::Ready(res);
+		}
+
+		slot.waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+
+	/// Allocates the AIO object and starts the operation on it.
+	fn begin(&mut self, start: Start) -> std::result::Result<(), AioResult>
+	{
+		let shared = Arc::clone(&self.shared);
+		let aio = match Aio::new(move |_, res| Slot::complete(&shared, res)) {
+			Ok(a) => a,
+			Err(e) => return Err(start.fail(e)),
+		};
+
+		if let Err(e) = aio.set_timeout(self.timeout) {
+			return Err(start.fail(e));
+		}
+
+		let res = match (start, &self.target) {
+			(Start::Recv, Target::Socket(s)) => {
+				aio.recv_socket(s).map_err(|e| AioResult::Recv(Err(e)))
+			},
+			(Start::Recv, Target::Context(c)) => {
+				aio.recv_ctx(c).map_err(|e| AioResult::Recv(Err(e)))
+			},
+			(Start::Send(m), Target::Socket(s)) => {
+				aio.send_socket(s, m).map_err(|e| AioResult::Send(Err(e)))
+			},
+			(Start::Send(m), Target::Context(c)) => {
+				aio.send_ctx(c, m).map_err(|e| AioResult::Send(Err(e)))
+			},
+		};
+
+		self.aio = Some(aio);
+		res
+	}
+}
+
+impl Drop for Operation
+{
+	fn drop(&mut self)
+	{
+		// Waiting for the operation also waits for the callback to finish, which means the
+		// callback's handle to the AIO is gone by the time ours is dropped.
+		if let Some(aio) = &self.aio {
+			aio.cancel();
+			aio.wait();
+		}
+	}
+}
+
+/// The result of an operation and the task waiting on it.
+#[derive(Debug, Default)]
+struct Slot
+{
+	/// The result of the operation, if it has completed.
+	result: Option<AioResult>,
+
+	/// The waker of the task that last polled the operation.
+	waker: Option<Waker>,
+}
+impl Slot
+{
+	/// Stores the result of the operation and wakes the waiting task.
+	fn complete(shared: &Mutex<Slot>, res: AioResult)
+	{
+		let waker = {
+			let mut slot = shared.lock().unwrap();
+			slot.result = Some(res);
+			slot.waker.take()
+		};
+
+		if let Some(w) = waker {
+			w.wake();
+		}
+	}
+}
+
+/// Runs the future to completion, blocking the current thread.
+#[allow(clippy::redundant_pub_crate)] // Makes it clear that this is not part of the public API
+pub(crate) fn block_on<F: Future + Unpin>(mut fut: F) -> F::Output
+{
+	let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+	let mut cx = TaskContext::from_waker(&waker);
+
+	loop {
+		match Pin::new(&mut fut).poll(&mut cx) {
+			Poll::Ready(res) => return res,
+			Poll::Pending => thread::park(),
+		}
+	}
+}
+
+/// A waker that unparks a thread.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker
+{
+	fn wake(self: Arc<Self>) { self.0.unpark() }
+
+	fn wake_by_ref(self: &Arc<Self>) { self.0.unpark() }
+}
@@ -0,0 +1,398 @@
+use std::{
+	borrow::Cow,
+	ffi::{CStr, CString},
+	os::raw::{c_char, c_int, c_void},
+	ptr::{self, NonNull},
+	slice,
+};
+
+use crate::{
+	error::{Error, Result},
+	supplemental::*,
+	util::validate_ptr,
+};
+
+mod client;
+mod server;
+pub use self::{
+	client::HttpClient,
+	server::{HttpHandler, HttpServer},
+};
+
+/// Error string to unwrap if allocation fails.
+const ALLOC_FAIL_MSG: &str = "Failed to allocate an HTTP request or response";
+
+/// An HTTP request.
+///
+/// Requests are either created to be sent by an [`HttpClient`] or are received
+/// by the handlers of an [`HttpServer`].
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+///
+/// let mut req = HttpRequest::new("http://127.0.0.1:8080/api/items?limit=10")?;
+/// req.set_method("POST")?;
+/// req.set_header("Content-Type", "application/json")?;
+/// req.set_body(b"{}");
+///
+/// assert_eq!(req.method(), "POST");
+/// assert_eq!(req.uri(), "/api/items?limit=10");
+/// assert_eq!(req.header("host").unwrap(), "127.0.0.1:8080");
+/// assert_eq!(req.body(), b"{}");
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`HttpClient`]: struct.HttpClient.html
+/// [`HttpServer`]: struct.HttpServer.html
+#[derive(Debug)]
+pub struct HttpRequest
+{
+	/// The NNG request object.
+	req: NonNull<nng_http_req>,
+}
+impl HttpRequest
+{
+	/// Creates a new `GET` request for the URL.
+	///
+	/// The URI of the request is set to the path and query of the URL and the
+	/// `Host` header is set to its host.
+	///
+	/// # Errors
+	///
+	/// * [`AddressInvalid`]: An invalid URL was specified.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`AddressInvalid`]: enum.Error.html#variant.AddressInvalid
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn new(url: &str) -> Result<Self>
+	{
+		let url = Url::parse(url)?;
+		let mut reqp: *mut nng_http_req = ptr::null_mut();
+		let rv = unsafe { nng_http_req_alloc(&raw mut reqp, url.0.as_ptr()) };
+
+		validate_ptr(rv, reqp).map(HttpRequest::from_ptr)
+	}
+
+	/// Returns the method of the request, such as `GET`.
+	pub fn method(&self) -> Cow<'_, str>
+	{
+		unsafe { from_c_str(nng_http_req_get_method(self.req.as_ptr())) }
+	}
+
+	/// Returns the request URI, which is the path along with any query.
+	pub fn uri(&self) -> Cow<'_, str>
+	{
+		unsafe { from_c_str(nng_http_req_get_uri(self.req.as_ptr())) }
+	}
+
+	/// Returns the HTTP version of the request, such as `HTTP/1.1`.
+	pub fn version(&self) -> Cow<'_, str>
+	{
+		unsafe { from_c_str(nng_http_req_get_version(self.req.as_ptr())) }
+	}
+
+	/// Returns the value of the header with the given name, if it is present.
+	///
+	/// Header names are not case sensitive.
+	pub fn header(&self, name: &str) -> Option<Cow<'_, str>>
+	{
+		let name = CString::new(name).ok()?;
+		let ptr = unsafe { nng_http_req_get_header(self.req.as_ptr(), name.as_ptr()) };
+		if ptr.is_null() { None } else { Some(unsafe { from_c_str(ptr) }) }
+	}
+
+	/// Returns the body of the request.
+	pub fn body(&self) -> &[u8]
+	{
+		let mut data = ptr::null_mut();
+		let mut size = 0;
+		unsafe {
+			nng_http_req_get_data(self.req.as_ptr(), &raw mut data, &raw mut size);
+			from_raw_data(data, size)
+		}
+	}
+
+	/// Sets the method of the request.
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: The method contains a null byte.
+	///
+	///
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	pub fn set_method(&mut self, method: &str) -> Result<()>
+	{
+		let method = CString::new(method).map_err(|_| Error::InvalidInput)?;
+		let rv = unsafe { nng_http_req_set_method(self.req.as_ptr(), method.as_ptr()) };
+		rv2res!(rv).expect(ALLOC_FAIL_MSG);
+		Ok(())
+	}
+
+	/// Sets the request URI, which is the path along with any query.
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: The URI contains a null byte.
+	///
+	///
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	pub fn set_uri(&mut self, uri: &str) -> Result<()>
+	{
+		let uri = CString::new(uri).map_err(|_| Error::InvalidInput)?;
+		let rv = unsafe { nng_http_req_set_uri(self.req.as_ptr(), uri.as_ptr()) };
+		rv2res!(rv).expect(ALLOC_FAIL_MSG);
+		Ok(())
+	}
+
+	/// Sets the header with the given name, replacing any previous value.
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: The name or the value contains a null byte.
+	///
+	///
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	pub fn set_header(&mut self, name: &str, value: &str) -> Result<()>
+	{
+		let name = CString::new(name).map_err(|_| Error::InvalidInput)?;
+		let value = CString::new(value).map_err(|_| Error::InvalidInput)?;
+		let rv =
+			unsafe { nng_http_req_set_header(self.req.as_ptr(), name.as_ptr(), value.as_ptr()) };
+		rv2res!(rv).expect(ALLOC_FAIL_MSG);
+		Ok(())
+	}
+
+	/// Replaces the body of the request with a copy of the data.
+	///
+	/// The `Content-Length` header is updated to match.
+	pub fn set_body(&mut self, data: &[u8])
+	{
+		let rv = unsafe {
+			nng_http_req_copy_data(self.req.as_ptr(), data.as_ptr() as *const c_void, data.len())
+		};
+		rv2res!(rv).expect(ALLOC_FAIL_MSG);
+	}
+
+	/// Creates a new request wrapper around the provided NNG request.
+	const fn from_ptr(req: NonNull<nng_http_req>) -> Self { HttpRequest { req } }
+}
+
+impl Drop for HttpRequest
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			nng_http_req_free(self.req.as_ptr());
+		}
+	}
+}
+
+unsafe impl Send for HttpRequest {}
+
+unsafe impl Sync for HttpRequest {}
+
+/// An HTTP response.
+///
+/// The response starts out with an empty body and no headers other than those
+/// that NNG adds itself, such as `Content-Length`.
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+///
+/// let mut res = HttpResponse::new(200);
+/// res.set_header("Content-Type", "text/plain")?;
+/// res.set_body(b"Hello, Ferris");
+///
+/// assert_eq!(res.status(), 200);
+/// assert_eq!(res.header("content-type").unwrap(), "text/plain");
+/// assert_eq!(res.body(), b"Hello, Ferris");
+/// # Ok::<(), nng::Error>(())
+/// ```
+// As with messages, the only way that these functions can fail is if an allocation fails, which
+// is turned into a panic.
+#[derive(Debug)]
+pub struct HttpResponse
+{
+	/// The NNG response object.
+	res: NonNull<nng_http_res>,
+}
+impl HttpResponse
+{
+	/// Creates a new response with the given status code.
+	pub fn new(status: u16) -> Self
+	{
+		let mut res = HttpResponse::alloc(|resp| unsafe { nng_http_res_alloc(resp) });
+		res.set_status(status);
+		res
+	}
+
+	/// Creates an error response with the given status code.
+	///
+	/// The body of the response is filled in with a simple HTML error page.
+	pub fn error(status: u16) -> Self
+	{
+		HttpResponse::alloc(|resp| unsafe { nng_http_res_alloc_error(resp, status) })
+	}
+
+	/// Returns the status code of the response.
+	pub fn status(&self) -> u16 { unsafe { nng_http_res_get_status(self.res.as_ptr()) } }
+
+	/// Sets the status code of the response.
+	///
+	/// This also resets the reason phrase to the standard one for the code.
+	pub fn set_status(&mut self, status: u16)
+	{
+		let rv = unsafe { nng_http_res_set_status(self.res.as_ptr(), status) };
+		rv2res!(rv).expect(ALLOC_FAIL_MSG);
+	}
+
+	/// Returns the reason phrase of the response, such as `OK`.
+	pub fn reason(&self) -> Cow<'_, str>
+	{
+		unsafe { from_c_str(nng_http_res_get_reason(self.res.as_ptr())) }
+	}
+
+	/// Sets a custom reason phrase for the response.
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: The reason contains a null byte.
+	///
+	///
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	pub fn set_reason(&mut self, reason: &str) -> Result<()>
+	{
+		let reason = CString::new(reason).map_err(|_| Error::InvalidInput)?;
+		let rv = unsafe { nng_http_res_set_reason(self.res.as_ptr(), reason.as_ptr()) };
+		rv2res!(rv).expect(ALLOC_FAIL_MSG);
+		Ok(())
+	}
+
+	/// Returns the value of the header with the given name, if it is present.
+	///
+	/// Header names are not case sensitive.
+	pub fn header(&self, name: &str) -> Option<Cow<'_, str>>
+	{
+		let name = CString::new(name).ok()?;
+		let ptr = unsafe { nng_http_res_get_header(self.res.as_ptr(), name.as_ptr()) };
+		if ptr.is_null() { None } else { Some(unsafe { from_c_str(ptr) }) }
+	}
+
+	/// Sets the header with the given name, replacing any previous value.
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: The name or the value contains a null byte.
+	///
+	///
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	pub fn set_header(&mut self, name: &str, value: &str) -> Result<()>
+	{
+		let name = CString::new(name).map_err(|_| Error::InvalidInput)?;
+		let value = CString::new(value).map_err(|_| Error::InvalidInput)?;
+		let rv =
+			unsafe { nng_http_res_set_header(self.res.as_ptr(), name.as_ptr(), value.as_ptr()) };
+		rv2res!(rv).expect(ALLOC_FAIL_MSG);
+		Ok(())
+	}
+
+	/// Returns the body of the response.
+	pub fn body(&self) -> &[u8]
+	{
+		let mut data = ptr::null_mut();
+		let mut size = 0;
+		unsafe {
+			nng_http_res_get_data(self.res.as_ptr(), &raw mut data, &raw mut size);
+			from_raw_data(data, size)
+		}
+	}
+
+	/// Replaces the body of the response with a copy of the data.
+	///
+	/// The `Content-Length` header is updated to match.
+	pub fn set_body(&mut self, data: &[u8])
+	{
+		let rv = unsafe {
+			nng_http_res_copy_data(self.res.as_ptr(), data.as_ptr() as *const c_void, data.len())
+		};
+		rv2res!(rv).expect(ALLOC_FAIL_MSG);
+	}
+
+	/// Allocates a response using the provided NNG function.
+	fn alloc<F: FnOnce(*mut *mut nng_http_res) -> c_int>(f: F) -> Self
+	{
+		let mut resp: *mut nng_http_res = ptr::null_mut();
+		let rv = f(&raw mut resp);
+
+		let res = validate_ptr(rv, resp).expect(ALLOC_FAIL_MSG);
+		HttpResponse { res }
+	}
+
+	/// Consumes the response and returns the NNG response object.
+	fn into_ptr(self) -> NonNull<nng_http_res>
+	{
+		let res = self.res;
+		std::mem::forget(self);
+		res
+	}
+}
+
+impl Drop for HttpResponse
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			nng_http_res_free(self.res.as_ptr());
+		}
+	}
+}
+
+unsafe impl Send for HttpResponse {}
+
+unsafe impl Sync for HttpResponse {}
+
+/// A parsed URL, as used to create servers, clients and requests.
+struct Url(NonNull<nng_sys::nng_url>);
+impl Url
+{
+	/// Parses the URL, reporting any failure as an invalid address.
+	fn parse(url: &str) -> Result<Self>
+	{
+		let url = CString::new(url).map_err(|_| Error::AddressInvalid)?;
+		let mut urlp: *mut nng_sys::nng_url = ptr::null_mut();
+		let rv = unsafe { nng_sys::nng_url_parse(&raw mut urlp, url.as_ptr()) };
+
+		validate_ptr(rv, urlp).map(Url).map_err(|e| match e {
+			Error::InvalidInput => Error::AddressInvalid,
+			e => e,
+		})
+	}
+}
+
+impl Drop for Url
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			nng_sys::nng_url_free(self.0.as_ptr());
+		}
+	}
+}
+
+/// Converts a C string owned by a request or response, treating null as empty.
+unsafe fn from_c_str<'a>(ptr: *const c_char) -> Cow<'a, str>
+{
+	if ptr.is_null() { Cow::Borrowed("") } else { CStr::from_ptr(ptr).to_string_lossy() }
+}
+
+/// Converts a body owned by a request or response into a slice.
+unsafe fn from_raw_data<'a>(data: *mut c_void, size: usize) -> &'a [u8]
+{
+	if data.is_null() { &[] } else { slice::from_raw_parts(data as *const u8, size) }
+}
@@ -0,0 +1,294 @@
+use std::{
+	os::raw::c_void,
+	ptr::{self, NonNull},
+	sync::Arc,
+	time::Duration,
+};
+
+use super::{HttpRequest, HttpResponse, Url};
+use crate::{
+	error::Result,
+	supplemental::*,
+	tls::TlsConfig,
+	util::{abort_unwind, duration_to_nng, validate_ptr},
+};
+
+/// The type of the closure called when an asynchronous transaction completes.
+type TransactFn = dyn FnOnce(Result<HttpResponse>) + Send + 'static;
+
+/// An HTTP client provided by NNG.
+///
+/// The client is bound to the scheme, host and port of a URL and sends
+/// requests to that server over a new connection for each transaction.
+/// Transactions can either block the calling thread or run in the background
+/// and report their result to a closure.
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+///
+/// let server = HttpServer::new("http://127.0.0.1:0")?;
+/// server.add_handler(HttpHandler::static_content("/status", b"up", Some("text/plain"))?)?;
+/// server.start()?;
+///
+/// # let port = match server.local_addr()? { SocketAddr::Inet(a) => a.port(), _ => unreachable!() };
+/// let url = format!("http://127.0.0.1:{}/status", port);
+/// let client = HttpClient::new(&url)?;
+///
+/// let res = client.transact(&HttpRequest::new(&url)?)?;
+/// assert_eq!(res.status(), 200);
+/// assert_eq!(res.header("Content-Type").unwrap(), "text/plain");
+/// assert_eq!(res.body(), b"up");
+/// # Ok::<(), nng::Error>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct HttpClient
+{
+	/// The NNG client, which is kept alive by any outstanding transactions.
+	inner: Arc<Inner>,
+
+	/// The timeout of transactions made by this handle.
+	timeout: Option<Duration>,
+}
+impl HttpClient
+{
+	/// Creates a new client for the server in the URL.
+	///
+	/// Only the scheme, host and port of the URL are used.
+	///
+	/// # Errors
+	///
+	/// * [`AddressInvalid`]: An invalid URL was specified.
+	/// * [`NotSupported`]: The scheme is not supported, such as `https` when
+	///   NNG was built without TLS support.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`AddressInvalid`]: enum.Error.html#variant.AddressInvalid
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn new(url: &str) -> Result<Self>
+	{
+		let url = Url::parse(url)?;
+		let mut clientp: *mut nng_http_client = ptr::null_mut();
+		let rv = unsafe { nng_http_client_alloc(&raw mut clientp, url.0.as_ptr()) };
+
+		let client = validate_ptr(rv, clientp)?;
+		Ok(HttpClient { inner: Arc::new(Inner { client }), timeout: None })
+	}
+
+	/// Sets the TLS configuration of an `https` client.
+	///
+	/// The configuration should be in client mode and applies to every handle
+	/// to the client. Without it, the client fails to verify any server. See
+	/// [`HttpServer::set_tls`] for an example.
+	///
+	/// # Errors
+	///
+	/// * [`NotSupported`]: The client is not an `https` client.
+	///
+	///
+	/// [`HttpServer::set_tls`]: struct.HttpServer.html#method.set_tls
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	pub fn set_tls(&self, config: &TlsConfig) -> Result<()>
+	{
+		let rv = unsafe { nng_http_client_set_tls(self.inner.client.as_ptr(), config.as_ptr()) };
+		rv2res!(rv)
+	}
+
+	/// Sets the timeout of the transactions made by this handle.
+	///
+	/// The timeout covers the whole transaction, from connecting to the server
+	/// to receiving the body of the response. A value of `None` means that the
+	/// transactions never time out, which is the default.
+	pub fn set_timeout(&mut self, dur: Option<Duration>) { self.timeout = dur; }
+
+	/// Sends the request to the server and waits for the response.
+	///
+	/// NNG adds a `Connection: close` header to the request, as each
+	/// transaction uses its own connection.
+	///
+	/// # Errors
+	///
+	/// * [`ConnectionRefused`]: The server refused the connection.
+	/// * [`ConnectionReset`]: The server closed the connection.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	/// * [`Protocol`]: The server sent an invalid response.
+	/// * [`TimedOut`]: The transaction did not complete before the timeout.
+	///
+	///
+	/// [`ConnectionRefused`]: enum.Error.html#variant.ConnectionRefused
+	/// [`ConnectionReset`]: enum.Error.html#variant.ConnectionReset
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`Protocol`]: enum.Error.html#variant.Protocol
+	/// [`TimedOut`]: enum.Error.html#variant.TimedOut
+	pub fn transact(&self, req: &HttpRequest) -> Result<HttpResponse>
+	{
+		let res = HttpResponse::alloc(|resp| unsafe { nng_http_res_alloc(resp) });
+
+		let mut aiop: *mut nng_sys::nng_aio = ptr::null_mut();
+		let rv = unsafe { nng_sys::nng_aio_alloc(&raw mut aiop, None, ptr::null_mut()) };
+		let aio = validate_ptr(rv, aiop)?;
+
+		let rv = unsafe {
+			nng_sys::nng_aio_set_timeout(aio.as_ptr(), duration_to_nng(self.timeout));
+			nng_http_client_transact(
+				self.inner.client.as_ptr(),
+				req.req.as_ptr(),
+				res.res.as_ptr(),
+				aio.as_ptr(),
+			);
+			nng_sys::nng_aio_wait(aio.as_ptr());
+
+			let rv = nng_sys::nng_aio_result(aio.as_ptr());
+			nng_sys::nng_aio_free(aio.as_ptr());
+			rv
+		};
+
+		rv2res!(rv, res)
+	}
+
+	/// Sends the request to the server in the background.
+	///
+	/// The closure is called with the response, or the reason that the
+	/// transaction failed, once the transaction completes. It is called on an
+	/// NNG thread and so it should not block. If the closure panics, the
+	/// program will log the panic if possible and then abort, as with [`Aio`]
+	/// callbacks.
+	///
+	/// The client is kept alive until the transaction completes, even if every
+	/// handle to it has been dropped.
+	///
+	/// # Errors
+	///
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	/// The closure receives the same errors as [`transact`].
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use nng::*;
+	/// use std::sync::mpsc;
+	///
+	/// let server = HttpServer::new("http://127.0.0.1:0")?;
+	/// let mut echo = HttpHandler::new("/echo", |req| {
+	///     let mut res = HttpResponse::new(200);
+	///     res.set_body(req.body());
+	///     res
+	/// })?;
+	/// echo.set_method(Some("POST"))?;
+	/// server.add_handler(echo)?;
+	/// server.start()?;
+	///
+	/// # let port = match server.local_addr()? { SocketAddr::Inet(a) => a.port(), _ => unreachable!() };
+	/// let url = format!("http://127.0.0.1:{}/echo", port);
+	/// let client = HttpClient::new(&url)?;
+	///
+	/// let mut req = HttpRequest::new(&url)?;
+	/// req.set_method("POST")?;
+	/// req.set_body(b"Ferris");
+	///
+	/// let (tx, rx) = mpsc::channel();
+	/// client.transact_async(req, move |res| tx.send(res).unwrap())?;
+	///
+	/// let res = rx.recv().unwrap()?;
+	/// assert_eq!(res.body(), b"Ferris");
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`Aio`]: struct.Aio.html
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`transact`]: #method.transact
+	pub fn transact_async<F>(&self, req: HttpRequest, callback: F) -> Result<()>
+	where
+		F: FnOnce(Result<HttpResponse>) + Send + 'static,
+	{
+		let txn = Box::new(Transaction {
+			aio: ptr::null_mut(),
+			client: Arc::clone(&self.inner),
+			req,
+			res: HttpResponse::alloc(|resp| unsafe { nng_http_res_alloc(resp) }),
+			callback: Box::new(callback),
+		});
+		let (client, request, response) = (txn.client.client, txn.req.req, txn.res.res);
+		let txn = Box::into_raw(txn);
+
+		let mut aiop: *mut nng_sys::nng_aio = ptr::null_mut();
+		let rv = unsafe {
+			nng_sys::nng_aio_alloc(&raw mut aiop, Some(Self::trampoline), txn as *mut c_void)
+		};
+		if let Err(e) = validate_ptr(rv, aiop) {
+			unsafe { drop(Box::from_raw(txn)) };
+			return Err(e);
+		}
+
+		// The transaction belongs to the callback from this point on, which might run before
+		// the call to start the transaction has returned.
+		unsafe {
+			(*txn).aio = aiop;
+			nng_sys::nng_aio_set_timeout(aiop, duration_to_nng(self.timeout));
+			nng_http_client_transact(client.as_ptr(), request.as_ptr(), response.as_ptr(), aiop);
+		}
+
+		Ok(())
+	}
+
+	/// Trampoline function for completing an asynchronous transaction.
+	extern "C" fn trampoline(arg: *mut c_void)
+	{
+		abort_unwind(|| unsafe {
+			let txn = Box::from_raw(arg as *mut Transaction);
+			let rv = nng_sys::nng_aio_result(txn.aio);
+
+			// The AIO can't be freed from its own callback, so it has to be done later.
+			nng_sys::nng_aio_reap(txn.aio);
+
+			let Transaction { res, callback, .. } = *txn;
+			callback(rv2res!(rv, res));
+		});
+	}
+}
+
+/// The NNG client shared by all handles and running transactions.
+#[derive(Debug)]
+struct Inner
+{
+	/// The NNG client object.
+	client: NonNull<nng_http_client>,
+}
+
+impl Drop for Inner
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			nng_http_client_free(self.client.as_ptr());
+		}
+	}
+}
+
+unsafe impl Send for Inner {}
+
+unsafe impl Sync for Inner {}
+
+/// The state of an asynchronous transaction.
+struct Transaction
+{
+	/// The AIO running the transaction.
+	aio: *mut nng_sys::nng_aio,
+
+	/// The client, which has to outlive the transaction.
+	client: Arc<Inner>,
+
+	/// The request being sent, which NNG uses until the transaction completes.
+	req: HttpRequest,
+
+	/// The response that NNG fills in.
+	res: HttpResponse,
+
+	/// The closure to call with the result.
+	callback: Box<TransactFn>,
+}
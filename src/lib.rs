@@ -20,7 +20,7 @@
 //!
 //! ### Rust Version Requirements
 //!
-//! The current version requires **Rustc v1.36 or greater**. In general, this
+//! The current version requires **Rustc v1.82 or greater**. In general, this
 //! crate should always be able to compile with the Rustc version available on
 //! the oldest currently-supported Ubuntu LTS release. Changes to the minimum
 //! required Rustc version will only be considered a breaking change if the
@@ -35,11 +35,25 @@
 //!
 //! * `build-nng` (default): Build NNG from source and statically link to the
 //!   library.
+//! * `tls`: Build NNG with TLS support, which enables the `tls+tcp` and `wss`
+//!   transports and `TlsConfig`. NNG uses Mbed TLS as its TLS engine, which
+//!   must be installed on the system.
 //! * `ffi-module`: Expose the raw FFI bindings via the `nng::ffi` module. This
 //!   is useful for utilizing NNG features that are implemented in the base
 //!   library but not this wrapper. Note that this exposes some internal items
 //!   of this library and it directly exposes the NNG library, so anything
 //!   enabled by this can change without bumping versions.
+//! * `futures`: Implement the `Stream` and `Sink` traits from the `futures`
+//!   ecosystem for sockets and contexts via the `MessageStream` adapter, and
+//!   the `AsyncRead` and `AsyncWrite` traits for byte streams via the
+//!   `AsyncStream` adapter.
+//! * `tokio`: Implement the `AsyncRead` and `AsyncWrite` traits from `tokio`
+//!   for byte streams via the `AsyncStream` adapter.
+//! * `mio`: Implement the `Source` trait from `mio` for the `Readiness`
+//!   handles of sockets, on Unix platforms.
+//! * `serde`: Serialize values directly into messages via the `codec` module.
+//!   The `codec-json`, `codec-cbor`, `codec-bincode` and `codec-msgpack`
+//!   features each enable `serde` along with the matching codec.
 //!
 //! ### Building NNG
 //!
@@ -167,34 +181,73 @@
 #[macro_use]
 mod util;
 
+#[cfg(feature = "futures")]
+mod adapter;
 mod addr;
 mod aio;
+mod backtrace;
 mod ctx;
 mod device;
 mod dialer;
 mod error;
+mod future;
+mod http;
 mod listener;
 mod message;
 mod pipe;
 mod protocol;
+#[cfg(unix)]
+mod readiness;
+mod reqrep;
 mod socket;
+mod stats;
+mod stream;
+mod supplemental;
+mod survey;
+mod tls;
 
+#[cfg(feature = "serde")]
+pub mod codec;
 pub mod options;
 
 pub use crate::{
 	addr::SocketAddr,
 	aio::{Aio, AioResult},
+	backtrace::Backtrace,
 	ctx::Context,
-	device::{forwarder, reflector},
+	device::{forwarder, reflector, Device, Direction},
 	dialer::{Dialer, DialerBuilder},
 	error::{Error, Result},
+	future::{RecvFuture, SendFuture},
+	http::{HttpClient, HttpHandler, HttpRequest, HttpResponse, HttpServer},
 	listener::{Listener, ListenerBuilder},
 	message::{Header, Message},
-	pipe::{Pipe, PipeEvent},
+	pipe::{Pipe, PipeEvent, PipeInfo},
 	protocol::Protocol,
-	socket::{RawSocket, Socket},
+	reqrep::{CallFuture, RepServer, RepServerBuilder, ReqClient},
+	socket::{
+		Bus0Socket, Pair0Socket, Pair1Socket, PipeEvents, PipeSubscription, Pub0Socket,
+		Pull0Socket, Push0Socket, RawSocket, Rep0Socket, Req0Socket, Respondent0Socket, Socket,
+		Sub0Socket, Surveyor0Socket,
+	},
+	stats::{PrometheusExporter, Stat, StatChildren, StatKind, StatUnit, StatValue, Stats},
+	stream::{Stream, StreamDialer, StreamListener},
+	survey::{Responses, Surveyor},
+	tls::{TlsAuthMode, TlsConfig, TlsMode, TlsVersion},
 };
 
+#[cfg(feature = "futures")]
+pub use crate::{
+	adapter::MessageStream,
+	survey::{ResponseStream, SurveyFuture},
+};
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+pub use crate::stream::AsyncStream;
+
+#[cfg(unix)]
+pub use crate::readiness::{PollEvent, PollInterest, Poller, Readiness};
+
 #[cfg(feature = "ffi-module")]
 /// Raw NNG foreign function interface.
 pub use nng_sys as ffi;
@@ -8,6 +8,7 @@ use std::{
 use crate::{
 	error::{Error, Result},
 	socket::Socket,
+	stats::{self, Stats},
 };
 
 /// Active listener for incoming connections.
@@ -99,6 +100,27 @@ impl Listener
 		);
 	}
 
+	/// Takes a snapshot of the statistics of this listener.
+	///
+	/// The root of the snapshot is the scope of the listener. See [`Stats`] for
+	/// more information.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The listener is not open.
+	/// * [`NotSupported`]: NNG was built without support for statistics.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`Stats`]: struct.Stats.html
+	pub fn stats(self) -> Result<Stats>
+	{
+		stats::scoped("listener", unsafe { nng_sys::nng_listener_id(self.handle) })
+	}
+
 	/// Create a new `Listener` handle from a NNG handle.
 	///
 	/// This function will panic if the handle is not valid.
@@ -162,6 +184,7 @@ expose_options!{
 	GETOPT_BOOL = nng_sys::nng_listener_get_bool;
 	GETOPT_INT = nng_sys::nng_listener_get_int;
 	GETOPT_MS = nng_sys::nng_listener_get_ms;
+	GETOPT_PTR = nng_sys::nng_listener_get_ptr;
 	GETOPT_SIZE = nng_sys::nng_listener_get_size;
 	GETOPT_SOCKADDR = nng_sys::nng_listener_get_addr;
 	GETOPT_STRING = nng_sys::nng_listener_get_string;
@@ -282,6 +305,7 @@ expose_options!{
 	GETOPT_BOOL = nng_sys::nng_listener_get_bool;
 	GETOPT_INT = nng_sys::nng_listener_get_int;
 	GETOPT_MS = nng_sys::nng_listener_get_ms;
+	GETOPT_PTR = nng_sys::nng_listener_get_ptr;
 	GETOPT_SIZE = nng_sys::nng_listener_get_size;
 	GETOPT_SOCKADDR = nng_sys::nng_listener_get_addr;
 	GETOPT_STRING = nng_sys::nng_listener_get_string;
@@ -307,6 +331,7 @@ expose_options!{
 	         transport::tcp::KeepAlive,
 	         transport::tls::CaFile,
 	         transport::tls::CertKeyFile,
+	         transport::tls::Config,
 	         transport::websocket::ResponseHeaders,
 	         transport::websocket::Protocol];
 }
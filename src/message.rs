@@ -6,11 +6,67 @@ use std::{
 	slice::{self, SliceIndex},
 };
 
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+
 use crate::{pipe::Pipe, util::validate_ptr};
 
+#[cfg(feature = "serde")]
+use crate::codec::{Codec, CodecError};
+
 /// Error string to unwrap if allocation fails.
 const ALLOC_FAIL_MSG: &str = "NNG failed to allocate memory";
 
+/// Implements the big-endian integer functions using the given NNG functions.
+macro_rules! integer_ops
+{
+	($(
+		$int:ty:
+		$push_back:ident = $append:ident,
+		$push_front:ident = $insert:ident,
+		$pop_back:ident = $chop:ident,
+		$pop_front:ident = $trim:ident;
+	)*) => {$(
+		/// Appends the integer to the back in big-endian byte order.
+		pub fn $push_back(&mut self, val: $int)
+		{
+			let rv = unsafe { nng_sys::$append(self.msgp.as_ptr(), val) };
+			rv2res!(rv).expect(ALLOC_FAIL_MSG)
+		}
+
+		/// Prepends the integer to the front in big-endian byte order.
+		pub fn $push_front(&mut self, val: $int)
+		{
+			let rv = unsafe { nng_sys::$insert(self.msgp.as_ptr(), val) };
+			rv2res!(rv).expect(ALLOC_FAIL_MSG)
+		}
+
+		/// Removes a big-endian integer from the back.
+		///
+		/// If there are not enough bytes to hold the integer, this returns `None`
+		/// and the contents are left unchanged.
+		pub fn $pop_back(&mut self) -> Option<$int>
+		{
+			let mut val = 0;
+			let rv = unsafe { nng_sys::$chop(self.msgp.as_ptr(), &mut val) };
+
+			if rv == 0 { Some(val) } else { None }
+		}
+
+		/// Removes a big-endian integer from the front.
+		///
+		/// If there are not enough bytes to hold the integer, this returns `None`
+		/// and the contents are left unchanged.
+		pub fn $pop_front(&mut self) -> Option<$int>
+		{
+			let mut val = 0;
+			let rv = unsafe { nng_sys::$trim(self.msgp.as_ptr(), &mut val) };
+
+			if rv == 0 { Some(val) } else { None }
+		}
+	)*};
+}
+
 /// An NNG message type.
 ///
 /// Applications desiring to use the richest part of NNG will want to use the
@@ -20,6 +76,26 @@ const ALLOC_FAIL_MSG: &str = "NNG failed to allocate memory";
 /// In addition to the regular portion of the message there is a header that
 /// carries protocol specific header information. Most applications will not
 /// need to touch the header and will only interact with the regular message.
+///
+/// Both the body and the header can be read and written as big-endian
+/// integers, which is useful for framing and for routing headers:
+///
+/// ```
+/// use nng::Message;
+///
+/// let mut msg = Message::new();
+/// msg.push_back_u32(0xDEAD_BEEF);
+/// msg.push_front_u16(7);
+/// assert_eq!(&msg[..], &[0, 7, 0xDE, 0xAD, 0xBE, 0xEF]);
+///
+/// assert_eq!(msg.pop_front_u16(), Some(7));
+/// assert_eq!(msg.pop_back_u64(), None);
+/// assert_eq!(msg.pop_back_u32(), Some(0xDEAD_BEEF));
+///
+/// msg.as_mut_header().push_back_u32(0x8000_0001);
+/// assert_eq!(msg.as_mut_header().pop_front_u32(), Some(0x8000_0001));
+/// assert!(msg.as_header().is_empty());
+/// ```
 // None of these methods will report failure. As of this writing (NNG v1.2.3) the only possible
 // error condition in any of the message functions is if allocation, which is a regular `malloc` or
 // `calloc` call), fails. Both of the platforms this crate really supports do overcommit, which
@@ -172,6 +248,21 @@ impl Message
 		rv2res!(rv).expect(ALLOC_FAIL_MSG)
 	}
 
+	integer_ops! {
+		u16: push_back_u16 = nng_msg_append_u16,
+		     push_front_u16 = nng_msg_insert_u16,
+		     pop_back_u16 = nng_msg_chop_u16,
+		     pop_front_u16 = nng_msg_trim_u16;
+		u32: push_back_u32 = nng_msg_append_u32,
+		     push_front_u32 = nng_msg_insert_u32,
+		     pop_back_u32 = nng_msg_chop_u32,
+		     pop_front_u32 = nng_msg_trim_u32;
+		u64: push_back_u64 = nng_msg_append_u64,
+		     push_front_u64 = nng_msg_insert_u64,
+		     pop_back_u64 = nng_msg_chop_u64,
+		     pop_front_u64 = nng_msg_trim_u64;
+	}
+
 	/// Returns the pipe object associated with the message.
 	///
 	/// On receive, this is the pipe from which the message was received. On
@@ -219,6 +310,53 @@ impl Message
 	}
 }
 
+#[cfg(feature = "serde")]
+impl Message
+{
+	/// Creates a new message containing the serialized value.
+	///
+	/// The value is written directly into the message body, so no intermediate
+	/// buffer is allocated.
+	///
+	/// This function is only available when the `serde` feature is enabled.
+	///
+	/// # Errors
+	///
+	/// * [`CodecError::Encode`]: The value could not be serialized.
+	///
+	///
+	/// [`CodecError::Encode`]: codec/enum.CodecError.html#variant.Encode
+	#[allow(clippy::needless_pass_by_value)] // Codecs are usually unit structs
+	pub fn encode<T, C>(value: &T, codec: C) -> Result<Message, CodecError>
+	where
+		T: Serialize + ?Sized,
+		C: Codec,
+	{
+		let mut msg = Message::new();
+		codec.encode(value, &mut msg)?;
+
+		Ok(msg)
+	}
+
+	/// Deserializes a value from the message body.
+	///
+	/// The message header is not included in the deserialized bytes.
+	///
+	/// This function is only available when the `serde` feature is enabled.
+	///
+	/// # Errors
+	///
+	/// * [`CodecError::Decode`]: The body is not a valid value.
+	///
+	///
+	/// [`CodecError::Decode`]: codec/enum.CodecError.html#variant.Decode
+	#[allow(clippy::needless_pass_by_value)] // Codecs are usually unit structs
+	pub fn decode<T: DeserializeOwned, C: Codec>(&self, codec: C) -> Result<T, CodecError>
+	{
+		codec.decode(self.as_slice())
+	}
+}
+
 #[cfg(feature = "ffi-module")]
 impl Message
 {
@@ -496,6 +634,21 @@ impl Header
 
 		rv2res!(rv).expect(ALLOC_FAIL_MSG)
 	}
+
+	integer_ops! {
+		u16: push_back_u16 = nng_msg_header_append_u16,
+		     push_front_u16 = nng_msg_header_insert_u16,
+		     pop_back_u16 = nng_msg_header_chop_u16,
+		     pop_front_u16 = nng_msg_header_trim_u16;
+		u32: push_back_u32 = nng_msg_header_append_u32,
+		     push_front_u32 = nng_msg_header_insert_u32,
+		     pop_back_u32 = nng_msg_header_chop_u32,
+		     pop_front_u32 = nng_msg_header_trim_u32;
+		u64: push_back_u64 = nng_msg_header_append_u64,
+		     push_front_u64 = nng_msg_header_insert_u64,
+		     pop_back_u64 = nng_msg_header_chop_u64,
+		     pop_front_u64 = nng_msg_header_trim_u64;
+	}
 }
 unsafe impl Send for Header {}
 unsafe impl Sync for Header {}
@@ -1,15 +1,14 @@
 use std::{
 	ffi::{CStr, CString},
-	mem::MaybeUninit,
+	mem::{self, MaybeUninit},
 	os::raw::{c_char, c_int, c_void},
-	ptr,
+	ptr::{self, NonNull},
 	time::Duration,
 };
 
 use crate::{
 	addr::SocketAddr,
 	error::{Error, Result},
-	util::validate_ptr,
 };
 
 /// Exposes the ability to get and set the option.
@@ -42,6 +41,8 @@ pub trait HasOpts: Sized
 		*const c_char,
 		*mut nng_sys::nng_duration,
 	) -> c_int;
+	/// Raw NNG function for getting a pointer option.
+	const GETOPT_PTR: unsafe extern "C" fn(Self::Handle, *const c_char, *mut *mut c_void) -> c_int;
 	/// Raw NNG function for getting a `size_t` option.
 	const GETOPT_SIZE: unsafe extern "C" fn(Self::Handle, *const c_char, *mut usize) -> c_int;
 	/// Raw NNG function for getting an `nng_sockaddr` option.
@@ -134,7 +135,11 @@ pub trait HasOpts: Sized
 		unsafe {
 			let mut ptr: *mut c_char = ptr::null_mut();
 			let rv = (Self::GETOPT_STRING)(self.handle(), opt, &mut ptr as *mut _);
-			let ptr = validate_ptr(rv, ptr)?;
+			rv2res!(rv)?;
+
+			// Some options, such as the common name of a peer that did not present a
+			// certificate, successfully return no string at all.
+			let ptr = NonNull::new(ptr).ok_or(Error::EntryNotFound)?;
 
 			let name = CStr::from_ptr(ptr.as_ptr()).to_string_lossy().into_owned();
 			nng_sys::nng_strfree(ptr.as_ptr());
@@ -143,6 +148,36 @@ pub trait HasOpts: Sized
 		}
 	}
 
+	/// Get the strings of an option that is a null-terminated array of strings.
+	fn getopt_strings(&self, opt: *const c_char) -> Result<Vec<String>>
+	{
+		unsafe {
+			let mut ptr: *mut c_void = ptr::null_mut();
+			let rv = (Self::GETOPT_PTR)(self.handle(), opt, &raw mut ptr);
+			rv2res!(rv)?;
+
+			// NNG returns a null pointer rather than an empty array.
+			let arr = ptr as *mut *mut c_char;
+			if arr.is_null() {
+				return Ok(Vec::new());
+			}
+
+			let mut strings = Vec::new();
+			loop {
+				let s = *arr.add(strings.len());
+				if s.is_null() {
+					break;
+				}
+
+				strings.push(CStr::from_ptr(s).to_string_lossy().into_owned());
+				nng_sys::nng_strfree(s);
+			}
+			nng_sys::nng_free(arr as _, (strings.len() + 1) * mem::size_of::<*mut c_char>());
+
+			Ok(strings)
+		}
+	}
+
 	/// The the `u64` option.
 	fn getopt_uint64(&self, opt: *const c_char) -> Result<u64>
 	{
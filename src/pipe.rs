@@ -3,7 +3,14 @@ use std::{
 	hash::{Hash, Hasher},
 };
 
-use crate::{dialer::Dialer, listener::Listener};
+use crate::{
+	addr::SocketAddr,
+	dialer::Dialer,
+	error::Result,
+	listener::Listener,
+	options::{LocalAddr, Options, RemAddr},
+	stats::{self, Stats},
+};
 
 /// An NNG communication pipe.
 ///
@@ -71,6 +78,27 @@ impl Pipe
 		);
 	}
 
+	/// Takes a snapshot of the statistics of this pipe.
+	///
+	/// The root of the snapshot is the scope of the pipe. See [`Stats`] for
+	/// more information.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The pipe is not open.
+	/// * [`NotSupported`]: NNG was built without support for statistics.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`Stats`]: struct.Stats.html
+	pub fn stats(self) -> Result<Stats>
+	{
+		stats::scoped("pipe", unsafe { nng_sys::nng_pipe_id(self.handle) })
+	}
+
 	/// Returns the underlying NNG handle for the pipe.
 	pub(crate) const fn handle(self) -> nng_sys::nng_pipe { self.handle }
 
@@ -134,6 +162,7 @@ expose_options!{
 	GETOPT_BOOL = nng_sys::nng_pipe_get_bool;
 	GETOPT_INT = nng_sys::nng_pipe_get_int;
 	GETOPT_MS = nng_sys::nng_pipe_get_ms;
+	GETOPT_PTR = nng_sys::nng_pipe_get_ptr;
 	GETOPT_SIZE = nng_sys::nng_pipe_get_size;
 	GETOPT_SOCKADDR = nng_sys::nng_pipe_get_addr;
 	GETOPT_STRING = nng_sys::nng_pipe_get_string;
@@ -152,6 +181,8 @@ expose_options!{
 	         transport::tcp::NoDelay,
 	         transport::tcp::KeepAlive,
 	         transport::tls::Verified,
+	         transport::tls::PeerCn,
+	         transport::tls::PeerAltNames,
 	         transport::websocket::RequestHeaders,
 	         transport::websocket::ResponseHeaders];
 	Sets -> [];
@@ -212,3 +243,58 @@ impl PipeEvent
 		}
 	}
 }
+
+/// A snapshot of a pipe in the registry of a socket.
+///
+/// The information is captured when the pipe is added to the socket. See
+/// [`Socket::pipes`] for more information.
+///
+///
+/// [`Socket::pipes`]: struct.Socket.html#method.pipes
+#[derive(Clone, Debug)]
+pub struct PipeInfo
+{
+	/// The pipe itself.
+	pipe: Pipe,
+
+	/// The local address of the pipe, if the transport reports one.
+	local_addr: Option<SocketAddr>,
+
+	/// The remote address of the pipe, if the transport reports one.
+	remote_addr: Option<SocketAddr>,
+
+	/// The dialer that created the pipe, if any.
+	dialer: Option<Dialer>,
+
+	/// The listener that created the pipe, if any.
+	listener: Option<Listener>,
+}
+impl PipeInfo
+{
+	/// Captures the information of the pipe.
+	pub(crate) fn new(pipe: Pipe) -> Self
+	{
+		PipeInfo {
+			pipe,
+			local_addr: pipe.get_opt::<LocalAddr>().ok(),
+			remote_addr: pipe.get_opt::<RemAddr>().ok(),
+			dialer: pipe.dialer(),
+			listener: pipe.listener(),
+		}
+	}
+
+	/// Returns the pipe.
+	pub fn pipe(&self) -> Pipe { self.pipe }
+
+	/// Returns the local address of the pipe, if the transport reports one.
+	pub fn local_addr(&self) -> Option<&SocketAddr> { self.local_addr.as_ref() }
+
+	/// Returns the remote address of the pipe, if the transport reports one.
+	pub fn remote_addr(&self) -> Option<&SocketAddr> { self.remote_addr.as_ref() }
+
+	/// Returns the dialer that created the pipe, if any.
+	pub fn dialer(&self) -> Option<Dialer> { self.dialer }
+
+	/// Returns the listener that created the pipe, if any.
+	pub fn listener(&self) -> Option<Listener> { self.listener }
+}
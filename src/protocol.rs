@@ -138,5 +138,5 @@ pub enum Protocol
 #[allow(clippy::use_debug)]
 impl fmt::Display for Protocol
 {
-	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{self:?}") }
 }
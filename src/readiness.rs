@@ -0,0 +1,475 @@
+//! Readiness notifications for integrating sockets into event loops.
+use std::{
+	convert::TryFrom,
+	io,
+	os::{
+		raw::c_int,
+		unix::io::{AsRawFd, RawFd},
+	},
+	task::{Context as TaskContext, Poll},
+	time::{Duration, Instant},
+};
+
+use crate::{
+	error::{Error, Result},
+	socket::Socket,
+};
+
+mod reactor;
+
+#[allow(clippy::redundant_pub_crate)] // Makes it clear that this is not part of the public API
+pub(crate) use self::reactor::forget;
+
+/// A file descriptor that becomes readable when a socket is ready.
+///
+/// This is obtained from [`Socket::recv_readiness`] or
+/// [`Socket::send_readiness`] and wraps the descriptor of the [`RecvFd`] or
+/// [`SendFd`] option. Either way, the descriptor only ever becomes _readable_,
+/// which signals that the socket can receive or send a message without
+/// blocking. It can be registered with `poll`, `epoll` or `kqueue` alongside
+/// other descriptors and, with the `mio` feature, with a `mio` registry.
+///
+/// The descriptor belongs to the socket and must never be read from, written
+/// to or closed. The handle keeps the socket open until it is dropped, unless
+/// the socket is explicitly closed, after which the descriptor is no longer
+/// valid and must be removed from any event loop.
+///
+/// NNG clears the readiness once the socket has no messages to receive, or
+/// can no longer send, so a readable descriptor should be followed by calls to
+/// [`try_recv`] or [`try_send`] until they return [`TryAgain`].
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+/// use std::os::unix::io::AsRawFd;
+///
+/// let server = Socket::new(Protocol::Pair0)?;
+/// server.listen("inproc://nng/readiness")?;
+/// let ready = server.recv_readiness()?;
+///
+/// // The descriptor would be registered in an event loop here.
+/// let _fd = ready.as_raw_fd();
+///
+/// let client = Socket::new(Protocol::Pair0)?;
+/// client.dial("inproc://nng/readiness")?;
+/// client.send(&b"hello"[..]).map_err(|(_, e)| e)?;
+///
+/// # std::thread::sleep(std::time::Duration::from_millis(100));
+/// loop {
+///     match ready.socket().try_recv() {
+///         Ok(msg) => assert_eq!(&msg[..], b"hello"),
+///         Err(Error::TryAgain) => break,
+///         Err(e) => return Err(e),
+///     }
+/// }
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`RecvFd`]: options/struct.RecvFd.html
+/// [`SendFd`]: options/struct.SendFd.html
+/// [`Socket::recv_readiness`]: struct.Socket.html#method.recv_readiness
+/// [`Socket::send_readiness`]: struct.Socket.html#method.send_readiness
+/// [`TryAgain`]: enum.Error.html#variant.TryAgain
+/// [`try_recv`]: struct.Socket.html#method.try_recv
+/// [`try_send`]: struct.Socket.html#method.try_send
+#[derive(Clone, Debug)]
+pub struct Readiness
+{
+	/// The descriptor that NNG signals.
+	fd: RawFd,
+
+	/// The socket that owns the descriptor.
+	socket: Socket,
+}
+impl Readiness
+{
+	/// Creates a handle for a descriptor of the socket.
+	pub(crate) fn new(fd: RawFd, socket: Socket) -> Self { Readiness { fd, socket } }
+
+	/// Returns the socket that this handle belongs to.
+	pub fn socket(&self) -> &Socket { &self.socket }
+
+	/// Polls whether the descriptor is readable.
+	///
+	/// If it isn't, the task is woken once it is by a thread that this crate
+	/// starts the first time it is needed, so no particular runtime is
+	/// required. Closing the socket wakes the task as well. A task that stops
+	/// waiting is only forgotten once the socket becomes ready or is closed,
+	/// whichever happens first. The readiness may be gone again by the time
+	/// the task runs, such as when another task received the message first, in
+	/// which case the operation returns [`TryAgain`] and the readiness should
+	/// be polled again.
+	///
+	/// # Panics
+	///
+	/// Panics if the thread that wakes the tasks can't be started.
+	///
+	///
+	/// [`TryAgain`]: enum.Error.html#variant.TryAgain
+	pub fn poll_ready(&self, cx: &mut TaskContext) -> Poll<()>
+	{
+		if reactor::is_readable(self.fd) {
+			return Poll::Ready(());
+		}
+
+		// The reactor polls the descriptor as soon as it is registered, so it doesn't matter if
+		// it became readable in the meantime.
+		let id = unsafe { nng_sys::nng_socket_id(self.socket.handle()) };
+		reactor::get().register(id, self.fd, cx.waker());
+		Poll::Pending
+	}
+}
+
+impl AsRawFd for Readiness
+{
+	fn as_raw_fd(&self) -> RawFd { self.fd }
+}
+
+/// Registers the descriptor with a `mio` registry.
+///
+/// The descriptor is always registered for readable events, whatever the
+/// requested interests are, as that is the only way NNG signals readiness.
+/// As `mio` is edge triggered, every event has to be followed by calls to
+/// `try_recv` or `try_send` until they return `TryAgain`.
+///
+/// ## Example
+///
+/// ```
+/// use mio::{Events, Interest, Poll, Token};
+/// use nng::*;
+///
+/// let server = Socket::new(Protocol::Pair0)?;
+/// server.listen("inproc://nng/mio")?;
+/// let mut ready = server.recv_readiness()?;
+///
+/// let mut poll = Poll::new()?;
+/// poll.registry().register(&mut ready, Token(0), Interest::READABLE)?;
+///
+/// let client = Socket::new(Protocol::Pair0)?;
+/// client.dial("inproc://nng/mio")?;
+/// client.send(&b"hello"[..]).map_err(|(_, e)| e)?;
+///
+/// let mut events = Events::with_capacity(8);
+/// poll.poll(&mut events, None)?;
+/// assert_eq!(events.iter().next().unwrap().token(), Token(0));
+/// assert_eq!(&ready.socket().try_recv()?[..], b"hello");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "mio")]
+impl mio::event::Source for Readiness
+{
+	fn register(
+		&mut self,
+		registry: &mio::Registry,
+		token: mio::Token,
+		_: mio::Interest,
+	) -> std::io::Result<()>
+	{
+		mio::unix::SourceFd(&self.fd).register(registry, token, mio::Interest::READABLE)
+	}
+
+	fn reregister(
+		&mut self,
+		registry: &mio::Registry,
+		token: mio::Token,
+		_: mio::Interest,
+	) -> std::io::Result<()>
+	{
+		mio::unix::SourceFd(&self.fd).reregister(registry, token, mio::Interest::READABLE)
+	}
+
+	fn deregister(&mut self, registry: &mio::Registry) -> std::io::Result<()>
+	{
+		mio::unix::SourceFd(&self.fd).deregister(registry)
+	}
+}
+
+/// Waits for any of a set of sockets to become ready to receive or send.
+///
+/// Each socket is added with the readiness that is of interest and is
+/// identified by the token that [`add`] returns. Waiting uses `poll(2)` on the
+/// descriptors of the [`RecvFd`] and [`SendFd`] options, so a thread that
+/// serves many sockets can block until one of them has work for it instead of
+/// trying each in turn. The readiness is level triggered: a socket is reported
+/// for as long as it is ready, which means that the non-blocking
+/// [`try_recv`] and [`try_send`] functions can be used without draining it.
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+/// use std::time::Duration;
+///
+/// let mut poller = Poller::new();
+/// let mut servers = Vec::new();
+/// for i in 0..3 {
+///     let server = Socket::new(Protocol::Pull0)?;
+///     server.listen(&format!("inproc://nng/poller/{}", i))?;
+///     poller.add(&server, PollInterest::Recv)?;
+///     servers.push(server);
+/// }
+///
+/// let client = Socket::new(Protocol::Push0)?;
+/// client.dial("inproc://nng/poller/1")?;
+/// client.send(&b"work"[..]).map_err(|(_, e)| e)?;
+///
+/// let events = poller.wait(Some(Duration::from_secs(1)))?;
+/// assert_eq!(events.len(), 1);
+/// assert!(events[0].can_recv());
+///
+/// let socket = poller.socket(events[0].token()).unwrap();
+/// assert_eq!(&socket.try_recv()?[..], b"work");
+///
+/// // A socket that is closed is reported until it is removed.
+/// servers[2].close();
+/// let events = poller.wait(Some(Duration::from_secs(1)))?;
+/// assert!(events[0].is_closed());
+/// poller.remove(events[0].token());
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`add`]: #method.add
+/// [`RecvFd`]: options/struct.RecvFd.html
+/// [`SendFd`]: options/struct.SendFd.html
+/// [`try_recv`]: struct.Socket.html#method.try_recv
+/// [`try_send`]: struct.Socket.html#method.try_send
+#[derive(Debug, Default)]
+pub struct Poller
+{
+	/// The registered sockets, indexed by their token.
+	entries: Vec<Option<Entry>>,
+}
+impl Poller
+{
+	/// Creates a new poller without any sockets.
+	pub fn new() -> Self { Poller { entries: Vec::new() } }
+
+	/// Adds the socket to the poller and returns the token that identifies it.
+	///
+	/// The same socket can be added more than once, in which case each token
+	/// is reported separately. Tokens are never reused by the same poller.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The socket is not open.
+	/// * [`NotSupported`]: The protocol does not support receiving or sending,
+	///   as required by the interest.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	pub fn add(&mut self, socket: &Socket, interest: PollInterest) -> Result<usize>
+	{
+		let recv = match interest {
+			PollInterest::Recv | PollInterest::Both => Some(socket.recv_readiness()?),
+			PollInterest::Send => None,
+		};
+		let send = match interest {
+			PollInterest::Send | PollInterest::Both => Some(socket.send_readiness()?),
+			PollInterest::Recv => None,
+		};
+
+		self.entries.push(Some(Entry { socket: socket.clone(), recv, send }));
+		Ok(self.entries.len() - 1)
+	}
+
+	/// Removes the socket with the token from the poller and returns it.
+	///
+	/// Returns `None` if no socket with the token is in the poller.
+	pub fn remove(&mut self, token: usize) -> Option<Socket>
+	{
+		self.entries.get_mut(token).and_then(Option::take).map(|e| e.socket)
+	}
+
+	/// Returns the socket with the token.
+	///
+	/// Returns `None` if no socket with the token is in the poller.
+	pub fn socket(&self, token: usize) -> Option<&Socket>
+	{
+		self.entries.get(token).and_then(Option::as_ref).map(|e| &e.socket)
+	}
+
+	/// Blocks until at least one of the sockets is ready.
+	///
+	/// Returns an event for every socket that is ready. A timeout of `None`
+	/// waits forever. A socket that has been closed is reported as closed
+	/// rather than ready, and keeps being reported every time this is called
+	/// until it is removed from the poller. If a socket is already closed,
+	/// this does not block. A socket that is closed while waiting is reported
+	/// once the wait ends.
+	///
+	/// # Errors
+	///
+	/// * [`IncorrectState`]: The poller has no sockets and there is no timeout,
+	///   so it would wait forever.
+	/// * [`SystemErr`]: The call to `poll(2)` failed.
+	/// * [`TimedOut`]: None of the sockets became ready before the timeout.
+	///
+	///
+	/// [`IncorrectState`]: enum.Error.html#variant.IncorrectState
+	/// [`SystemErr`]: enum.Error.html#variant.SystemErr
+	/// [`TimedOut`]: enum.Error.html#variant.TimedOut
+	pub fn wait(&self, timeout: Option<Duration>) -> Result<Vec<PollEvent>>
+	{
+		if timeout.is_none() && self.entries.iter().all(Option::is_none) {
+			return Err(Error::IncorrectState);
+		}
+
+		// The descriptors of a closed socket might already belong to something else, so whether
+		// a socket is closed is taken from the socket itself and its descriptors aren't polled.
+		let closed: Vec<_> =
+			self.entries.iter().map(|e| e.as_ref().is_some_and(|e| e.socket.is_closed())).collect();
+		let mut fds = Vec::new();
+		for (entry, _) in self.entries.iter().zip(&closed).filter(|(_, &c)| !c) {
+			for r in entry.iter().flat_map(|e| e.recv.iter().chain(e.send.iter())) {
+				fds.push(libc::pollfd {
+					fd:      r.as_raw_fd(),
+					events:  libc::POLLIN,
+					revents: 0,
+				});
+			}
+		}
+
+		// There is no point in blocking when a closed socket can be reported right away.
+		let deadline = if closed.contains(&true) {
+			Some(Instant::now())
+		}
+		else {
+			timeout.map(|d| Instant::now() + d)
+		};
+
+		loop {
+			let ms = match deadline {
+				Some(d) => duration_to_ms(d.saturating_duration_since(Instant::now())),
+				None => -1,
+			};
+
+			let rv = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, ms) };
+			if rv >= 0 {
+				break;
+			}
+
+			let err = io::Error::last_os_error();
+			if err.kind() != io::ErrorKind::Interrupted {
+				return Err(Error::SystemErr(err.raw_os_error().unwrap_or(0) as u32));
+			}
+		}
+
+		// A socket that was closed while polling is reported as closed regardless of what
+		// `poll(2)` said about its descriptors.
+		let mut events = Vec::new();
+		let mut fds = fds.iter().map(|p| p.revents);
+		for (token, (entry, &was_closed)) in self.entries.iter().zip(&closed).enumerate() {
+			let Some(e) = entry
+			else {
+				continue;
+			};
+
+			// The descriptors of sockets that were already closed weren't polled.
+			let (recv, send) = if was_closed {
+				(0, 0)
+			}
+			else {
+				let recv = if e.recv.is_some() { fds.next().unwrap_or(0) } else { 0 };
+				let send = if e.send.is_some() { fds.next().unwrap_or(0) } else { 0 };
+				(recv, send)
+			};
+
+			let event = if was_closed || e.socket.is_closed() {
+				PollEvent { token, recv: false, send: false, closed: true }
+			}
+			else {
+				PollEvent { token, recv: recv != 0, send: send != 0, closed: false }
+			};
+
+			if event.recv || event.send || event.closed {
+				events.push(event);
+			}
+		}
+
+		if events.is_empty() {
+			return Err(Error::TimedOut);
+		}
+
+		Ok(events)
+	}
+}
+
+/// The readiness of a socket that a [`Poller`] waits for.
+///
+///
+/// [`Poller`]: struct.Poller.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum PollInterest
+{
+	/// The socket can receive a message.
+	Recv,
+
+	/// The socket can send a message.
+	Send,
+
+	/// The socket can either receive or send a message.
+	Both,
+}
+
+/// A socket that a [`Poller`] found to be ready.
+///
+///
+/// [`Poller`]: struct.Poller.html
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PollEvent
+{
+	/// The token of the socket.
+	token: usize,
+
+	/// Whether the socket can receive a message.
+	recv: bool,
+
+	/// Whether the socket can send a message.
+	send: bool,
+
+	/// Whether the socket has been closed.
+	closed: bool,
+}
+impl PollEvent
+{
+	/// Returns the token of the socket, as returned by `Poller::add`.
+	pub fn token(&self) -> usize { self.token }
+
+	/// Returns whether the socket can receive a message.
+	pub fn can_recv(&self) -> bool { self.recv }
+
+	/// Returns whether the socket can send a message.
+	pub fn can_send(&self) -> bool { self.send }
+
+	/// Returns whether the socket has been closed.
+	///
+	/// A closed socket can neither receive nor send and should be removed from
+	/// the poller, as it is otherwise reported by every call to `Poller::wait`.
+	pub fn is_closed(&self) -> bool { self.closed }
+}
+
+/// A socket in a `Poller`.
+#[derive(Debug)]
+struct Entry
+{
+	/// The socket itself.
+	socket: Socket,
+
+	/// The handle that signals the socket can receive, if that is of interest.
+	recv: Option<Readiness>,
+
+	/// The handle that signals the socket can send, if that is of interest.
+	send: Option<Readiness>,
+}
+
+/// Converts a duration to a `poll(2)` timeout, rounding up to whole
+/// milliseconds so that short timeouts do not become a busy loop.
+fn duration_to_ms(dur: Duration) -> c_int
+{
+	let ms = dur.as_millis() + u128::from(!dur.subsec_nanos().is_multiple_of(1_000_000));
+	c_int::try_from(ms).unwrap_or(c_int::MAX)
+}
@@ -0,0 +1,175 @@
+use std::{
+	collections::HashMap,
+	io,
+	os::{raw::c_int, unix::io::RawFd},
+	sync::{Mutex, OnceLock},
+	task::Waker,
+	thread,
+};
+
+/// The reactor, which is started on first use and then runs forever.
+static REACTOR: OnceLock<Reactor> = OnceLock::new();
+
+/// Returns the reactor, starting it if it isn't running yet.
+pub fn get() -> &'static Reactor
+{
+	// Only one thread runs the initializer, so the reactor thread is only started once.
+	let mut started = false;
+	let reactor = REACTOR.get_or_init(|| {
+		started = true;
+		Reactor::new()
+	});
+
+	if started {
+		thread::Builder::new()
+			.name(String::from("nng-reactor"))
+			.spawn(move || reactor.run())
+			.expect("Failed to start the reactor thread");
+	}
+
+	reactor
+}
+
+/// Wakes and forgets the tasks waiting on the socket, which has been closed.
+///
+/// This does not start the reactor if it isn't running yet, as nothing can be
+/// waiting on the socket in that case.
+pub fn forget(socket: c_int)
+{
+	if let Some(reactor) = REACTOR.get() {
+		reactor.forget(socket);
+	}
+}
+
+/// Returns whether the descriptor is readable right now.
+pub fn is_readable(fd: RawFd) -> bool
+{
+	let mut pfd = libc::pollfd { fd, events: libc::POLLIN, revents: 0 };
+	unsafe { libc::poll(&raw mut pfd, 1, 0) > 0 }
+}
+
+/// A thread that waits for descriptors to become readable and wakes the tasks
+/// waiting on them.
+pub struct Reactor
+{
+	/// The tasks to wake, keyed by the socket and the descriptor that they are
+	/// waiting on.
+	///
+	/// The socket is part of the key because the descriptor of a closed socket
+	/// can be reused by a new one.
+	waiting: Mutex<HashMap<(c_int, RawFd), Vec<Waker>>>,
+
+	/// The read end of the pipe that interrupts the reactor.
+	notify_rx: RawFd,
+
+	/// The write end of the pipe that interrupts the reactor.
+	notify_tx: RawFd,
+}
+impl Reactor
+{
+	/// Creates the state of the reactor.
+	fn new() -> Self
+	{
+		let mut fds: [c_int; 2] = [-1; 2];
+		let rv = unsafe { libc::pipe(fds.as_mut_ptr()) };
+		assert!(rv == 0, "Failed to create the reactor pipe ({})", io::Error::last_os_error());
+
+		for &fd in &fds {
+			unsafe {
+				libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+				libc::fcntl(fd, libc::F_SETFL, libc::O_NONBLOCK);
+			}
+		}
+
+		Reactor { waiting: Mutex::new(HashMap::new()), notify_rx: fds[0], notify_tx: fds[1] }
+	}
+
+	/// Wakes the task once the descriptor of the socket becomes readable or the
+	/// socket is closed.
+	pub fn register(&self, socket: c_int, fd: RawFd, waker: &Waker)
+	{
+		let mut waiting = self.waiting.lock().unwrap();
+		let wakers = waiting.entry((socket, fd)).or_default();
+		let new = wakers.is_empty();
+		if !wakers.iter().any(|w| w.will_wake(waker)) {
+			wakers.push(waker.clone());
+		}
+		drop(waiting);
+
+		// The reactor has to start polling a new descriptor.
+		if new {
+			self.notify();
+		}
+	}
+
+	/// Wakes and removes the tasks waiting on any descriptor of the socket.
+	fn forget(&self, socket: c_int)
+	{
+		let mut waiting = self.waiting.lock().unwrap();
+		let keys: Vec<_> = waiting.keys().filter(|k| k.0 == socket).copied().collect();
+		let closed: Vec<_> = keys.iter().filter_map(|k| waiting.remove(k)).flatten().collect();
+		drop(waiting);
+
+		// The reactor must stop polling the descriptors, which might be reused before it
+		// notices that they were closed.
+		if !keys.is_empty() {
+			self.notify();
+		}
+
+		for w in closed {
+			w.wake();
+		}
+	}
+
+	/// Interrupts the reactor so that it polls the current set of descriptors.
+	fn notify(&self)
+	{
+		// If the pipe is full, the reactor is already going to wake up.
+		unsafe { libc::write(self.notify_tx, [0u8].as_ptr() as *const _, 1) };
+	}
+
+	/// Polls the registered descriptors forever.
+	fn run(&self)
+	{
+		let mut keys = Vec::new();
+		let mut fds = Vec::new();
+		loop {
+			keys.clear();
+			keys.extend(self.waiting.lock().unwrap().keys().copied());
+
+			fds.clear();
+			fds.push(libc::pollfd { fd: self.notify_rx, events: libc::POLLIN, revents: 0 });
+			fds.extend(keys.iter().map(|&(_, fd)| libc::pollfd {
+				fd,
+				events: libc::POLLIN,
+				revents: 0,
+			}));
+
+			// Polling only fails when interrupted or short of memory, both of which are worth
+			// trying again.
+			let rv = unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, -1) };
+			if rv <= 0 {
+				continue;
+			}
+
+			if fds[0].revents != 0 {
+				let mut buf = [0u8; 64];
+				while unsafe { libc::read(self.notify_rx, buf.as_mut_ptr() as *mut _, buf.len()) }
+					> 0
+				{}
+			}
+
+			// A closed descriptor is reported as invalid, which wakes its tasks for good.
+			let mut ready = Vec::new();
+			let mut waiting = self.waiting.lock().unwrap();
+			for (key, _) in keys.iter().zip(&fds[1..]).filter(|(_, p)| p.revents != 0) {
+				ready.extend(waiting.remove(key).unwrap_or_default());
+			}
+			drop(waiting);
+
+			for w in ready {
+				w.wake();
+			}
+		}
+	}
+}
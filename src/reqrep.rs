@@ -0,0 +1,872 @@
+use std::{
+	fmt,
+	future::Future,
+	mem,
+	pin::Pin,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Condvar, Mutex,
+	},
+	task::{Context as TaskContext, Poll, Wake, Waker},
+	time::{Duration, Instant},
+};
+
+use crate::{
+	aio::{Aio, AioResult},
+	ctx::Context,
+	error::{Error, Result},
+	future::{self, RecvFuture, SendFuture},
+	message::Message,
+	options::{protocol::reqrep::ResendTime, Options},
+	protocol::Protocol,
+	socket::Socket,
+};
+use log::{error, warn};
+
+/// The number of requests a server handles concurrently unless told otherwise.
+const DEFAULT_PARALLEL: usize = 16;
+
+/// The boxed future produced by an asynchronous request handler.
+type HandlerFuture = Pin<Box<dyn Future<Output = Message> + Send + 'static>>;
+
+/// A concurrent request/reply server.
+///
+/// The server manages a pool of [`Context`] and [`Aio`] pairs on a _rep_
+/// socket, each of which runs the familiar "receive, handle, reply" loop. The
+/// size of the pool determines how many requests can be outstanding at once.
+/// It does *not* represent the number of threads in use: handlers are invoked
+/// from the NNG completion callbacks, so a synchronous handler should complete
+/// quickly and must never block for a significant amount of time. Long running
+/// work should use an asynchronous handler instead.
+///
+/// The server runs until it is stopped, either explicitly via
+/// [`RepServer::stop`] or by being dropped, or until the socket is closed.
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+///
+/// let socket = Socket::new(Protocol::Rep0)?;
+/// socket.listen("inproc://nng/reqrep/server")?;
+///
+/// let server = RepServerBuilder::new(&socket).parallel(4).start(|mut req| {
+///     req.push_front(b"Hello, ");
+///     req
+/// })?;
+///
+/// let client = Socket::new(Protocol::Req0)?;
+/// client.dial("inproc://nng/reqrep/server")?;
+/// client.send("Ferris".as_bytes())?;
+/// assert_eq!(&client.recv()?[..], b"Hello, Ferris");
+///
+/// server.stop();
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`Aio`]: struct.Aio.html
+/// [`Context`]: struct.Context.html
+/// [`RepServer::stop`]: struct.RepServer.html#method.stop
+pub struct RepServer
+{
+	/// The socket the server is running on.
+	socket: Socket,
+
+	/// The workers and the AIO objects that drive them.
+	workers: Vec<(Aio, Arc<Worker>)>,
+
+	/// State shared by all of the workers.
+	shared: Arc<Shared>,
+}
+impl RepServer
+{
+	/// Gracefully stops the server.
+	///
+	/// Workers that are waiting for a request are canceled while requests that
+	/// are currently being handled are allowed to finish and send their reply.
+	/// This function blocks until all workers have stopped. If there is no
+	/// request timeout configured, this means waiting on every outstanding
+	/// handler to complete.
+	///
+	/// The socket itself is not closed. Calling this function more than once
+	/// has no further effect.
+	///
+	/// This function must not be called from within a request handler.
+	#[allow(clippy::missing_panics_doc)]
+	pub fn stop(&self)
+	{
+		// The flag has to be set before looking at the workers: any worker that is
+		// not receiving right now will check it before posting its next receive.
+		self.shared.stopping.store(true, Ordering::SeqCst);
+		for (aio, worker) in &self.workers {
+			let state = worker.state.lock().unwrap();
+			if matches!(*state, State::Receiving) {
+				aio.cancel();
+			}
+			drop(state);
+		}
+
+		let mut active = self.shared.active.lock().unwrap();
+		while *active > 0 {
+			active = self.shared.finished.wait(active).unwrap();
+		}
+		drop(active);
+
+		// Workers mark themselves as finished from within their callback. Waiting for those
+		// callbacks to return makes sure that dropping the server never drops the last handle
+		// to an AIO from inside of its own callback, which would deadlock.
+		for (aio, _) in &self.workers {
+			aio.wait();
+		}
+	}
+
+	/// Returns the socket on which the server is running.
+	pub fn socket(&self) -> &Socket { &self.socket }
+}
+
+impl fmt::Debug for RepServer
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		f.debug_struct("RepServer")
+			.field("socket", &self.socket)
+			.field("parallel", &self.workers.len())
+			.field("request_timeout", &self.shared.timeout)
+			.finish()
+	}
+}
+
+impl Drop for RepServer
+{
+	fn drop(&mut self) { self.stop() }
+}
+
+/// Configuration utility for a [`RepServer`].
+///
+///
+/// [`RepServer`]: struct.RepServer.html
+#[derive(Debug)]
+pub struct RepServerBuilder
+{
+	/// The socket on which the server will run.
+	socket: Socket,
+
+	/// The number of workers to create.
+	parallel: usize,
+
+	/// The amount of time a handler has to produce a reply.
+	timeout: Option<Duration>,
+}
+impl RepServerBuilder
+{
+	/// Creates a new server configuration for the given socket.
+	///
+	/// The socket must have been opened with [`Protocol::Rep0`], which is
+	/// checked when the server is started.
+	///
+	///
+	/// [`Protocol::Rep0`]: enum.Protocol.html#variant.Rep0
+	pub fn new(socket: &Socket) -> Self
+	{
+		RepServerBuilder { socket: socket.clone(), parallel: DEFAULT_PARALLEL, timeout: None }
+	}
+
+	/// Sets the number of requests that can be handled concurrently.
+	///
+	/// Each one of these is a single `Context` and `Aio` pair and consumes a
+	/// small amount of memory. The default is 16 and a value of zero is
+	/// treated as one.
+	#[must_use]
+	pub fn parallel(mut self, n: usize) -> Self
+	{
+		self.parallel = n.max(1);
+		self
+	}
+
+	/// Sets the amount of time a handler has to produce a reply.
+	///
+	/// If the handler does not complete before the timeout expires, no reply is
+	/// sent, which leaves the requester to retry or give up as configured. The
+	/// future of an asynchronous handler is dropped as soon as the timeout
+	/// expires. Synchronous handlers cannot be interrupted, so they run to
+	/// completion and their late reply is discarded. By default, there is no
+	/// timeout.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use nng::*;
+	/// use std::{thread, time::Duration};
+	///
+	/// let socket = Socket::new(Protocol::Rep0)?;
+	/// socket.listen("inproc://nng/rep_server/timeout")?;
+	/// let _server = RepServerBuilder::new(&socket)
+	///     .request_timeout(Some(Duration::from_millis(10)))
+	///     .start(|msg| {
+	///         thread::sleep(Duration::from_millis(50));
+	///         msg
+	///     })?;
+	///
+	/// let client = ReqClient::new(Socket::new(Protocol::Req0)?)?;
+	/// client.socket().dial("inproc://nng/rep_server/timeout")?;
+	/// let res = client.call("slow".as_bytes(), Some(Duration::from_millis(200)));
+	/// assert_eq!(res.err(), Some(Error::TimedOut));
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	#[must_use]
+	pub fn request_timeout(mut self, dur: Option<Duration>) -> Self
+	{
+		self.timeout = dur;
+		self
+	}
+
+	/// Starts the server with a synchronous request handler.
+	///
+	/// The handler is invoked from within the NNG completion callbacks and must
+	/// complete quickly.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The socket is not open.
+	/// * [`NotSupported`]: The socket is not a cooked _rep_ socket.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	/// # Panics
+	///
+	/// If the handler panics, the program will log the panic if possible and
+	/// then abort, in the same manner as an [`Aio`] callback.
+	///
+	///
+	/// [`Aio`]: struct.Aio.html
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn start<F>(self, handler: F) -> Result<RepServer>
+	where
+		F: Fn(Message) -> Message + Send + Sync + 'static,
+	{
+		self.start_with(Handler::Sync(Box::new(handler)))
+	}
+
+	/// Starts the server with an asynchronous request handler.
+	///
+	/// The futures returned by the handler do not need any particular runtime.
+	/// They are polled from whichever thread wakes them, which is typically an
+	/// NNG callback thread, so each individual poll must complete quickly.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The socket is not open.
+	/// * [`NotSupported`]: The socket is not a cooked _rep_ socket.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	/// # Panics
+	///
+	/// The handler is called from within an NNG callback, so if it panics the
+	/// program will log the panic if possible and then abort, in the same
+	/// manner as an [`Aio`] callback. A panic while polling one of the futures
+	/// propagates to whichever thread woke it.
+	///
+	///
+	/// [`Aio`]: struct.Aio.html
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn start_async<F, R>(self, handler: F) -> Result<RepServer>
+	where
+		F: Fn(Message) -> R + Send + Sync + 'static,
+		R: Future<Output = Message> + Send + 'static,
+	{
+		self.start_with(Handler::Async(Box::new(move |msg| Box::pin(handler(msg)))))
+	}
+
+	/// Creates all of the workers and starts them receiving.
+	fn start_with(self, handler: Handler) -> Result<RepServer>
+	{
+		if self.socket.protocol() != Protocol::Rep0 {
+			return Err(Error::NotSupported);
+		}
+
+		let shared = Arc::new(Shared {
+			handler,
+			timeout: self.timeout,
+			stopping: AtomicBool::new(false),
+			active: Mutex::new(0),
+			finished: Condvar::new(),
+		});
+
+		let workers = (0..self.parallel)
+			.map(|_| {
+				let worker = Arc::new(Worker {
+					ctx:    Context::new(&self.socket)?,
+					state:  Mutex::new(State::Stopped),
+					shared: Arc::clone(&shared),
+				});
+
+				let cb_worker = Arc::clone(&worker);
+				let aio = Aio::new(move |aio, res| Worker::callback(&cb_worker, aio, res))?;
+				Ok((aio, worker))
+			})
+			.collect::<Result<Vec<_>>>()?;
+
+		// Only once everything has been allocated do we start receiving requests.
+		*shared.active.lock().unwrap() = workers.len();
+		for (aio, worker) in &workers {
+			worker.receive(aio);
+		}
+
+		Ok(RepServer { socket: self.socket, workers, shared })
+	}
+}
+
+/// A request/reply client that allows for many concurrent requests.
+///
+/// On its own, a _req_ socket only allows for a single outstanding request at
+/// a time. The client works around this by opening a new [`Context`] for every
+/// call, which means that any number of threads or tasks can issue requests on
+/// the same socket simultaneously. The client is cheap to clone and all clones
+/// share the same socket.
+///
+/// The context of each call takes the value of the [`ResendTime`] option that
+/// is set on the socket at the moment the call starts, unless the handle making
+/// the call has its own [resend time].
+///
+/// ## Example
+///
+/// ```
+/// use std::{thread, time::Duration};
+/// use nng::*;
+///
+/// let socket = Socket::new(Protocol::Rep0)?;
+/// socket.listen("inproc://nng/reqrep/client")?;
+/// let _server = RepServerBuilder::new(&socket).start(|req| req)?;
+///
+/// let client = ReqClient::new(Socket::new(Protocol::Req0)?)?;
+/// client.socket().dial("inproc://nng/reqrep/client")?;
+///
+/// let threads: Vec<_> = (0..4u8)
+///     .map(|i| {
+///         let client = client.clone();
+///         thread::spawn(move || client.call(&[i][..], Some(Duration::from_secs(1))))
+///     })
+///     .collect();
+///
+/// for (i, t) in threads.into_iter().enumerate() {
+///     assert_eq!(t.join().unwrap()?[0] as usize, i);
+/// }
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [resend time]: #method.set_resend_time
+/// [`Context`]: struct.Context.html
+/// [`ResendTime`]: options/protocol/reqrep/struct.ResendTime.html
+#[derive(Clone, Debug)]
+pub struct ReqClient
+{
+	/// The socket on which requests are made.
+	socket: Socket,
+
+	/// The resend time of the calls made by this handle, if it has been set.
+	#[allow(clippy::option_option)] // `None` is a valid resend time
+	resend: Option<Option<Duration>>,
+}
+impl ReqClient
+{
+	/// Creates a new client that issues requests on the given socket.
+	///
+	/// # Errors
+	///
+	/// * [`NotSupported`]: The socket was not opened with [`Protocol::Req0`].
+	///
+	///
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`Protocol::Req0`]: enum.Protocol.html#variant.Req0
+	pub fn new(socket: Socket) -> Result<Self>
+	{
+		if socket.protocol() != Protocol::Req0 {
+			return Err(Error::NotSupported);
+		}
+
+		Ok(ReqClient { socket, resend: None })
+	}
+
+	/// Sets the resend time of the calls made by this handle.
+	///
+	/// The request of a call is sent again if no reply has arrived after this
+	/// amount of time, which is set on the context of each call. A value of
+	/// `None` means that requests are never resent. Handles that have not set
+	/// a resend time use the [`ResendTime`] option of the socket. Note that NNG
+	/// only checks for requests to resend once a second.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use nng::*;
+	/// use std::{thread, time::Duration};
+	///
+	/// let server = Socket::new(Protocol::Rep0)?;
+	/// server.listen("inproc://nng/reqrep/resend")?;
+	/// thread::spawn(move || {
+	///     // The first request is lost and only the resent one is answered.
+	///     let _ = server.recv().unwrap();
+	///     let msg = server.recv().unwrap();
+	///     server.send(msg).unwrap();
+	/// });
+	///
+	/// let mut client = ReqClient::new(Socket::new(Protocol::Req0)?)?;
+	/// client.socket().dial("inproc://nng/reqrep/resend")?;
+	/// client.set_resend_time(Some(Duration::from_millis(50)));
+	///
+	/// let reply = client.call("ping".as_bytes(), Some(Duration::from_secs(5)))?;
+	/// assert_eq!(&reply[..], b"ping");
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`ResendTime`]: options/protocol/reqrep/struct.ResendTime.html
+	pub fn set_resend_time(&mut self, dur: Option<Duration>) { self.resend = Some(dur); }
+
+	/// Sends the request and blocks until the reply arrives.
+	///
+	/// The timeout covers the entire call, including any time spent resending
+	/// the request. A timeout of `None` waits forever.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The socket is not open.
+	/// * [`NotSupported`]: The socket was opened in raw mode.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	/// * [`TimedOut`]: No reply arrived before the timeout expired.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`TimedOut`]: enum.Error.html#variant.TimedOut
+	pub fn call<M: Into<Message>>(&self, msg: M, timeout: Option<Duration>) -> Result<Message>
+	{
+		future::block_on(self.call_fut(msg, timeout))
+	}
+
+	/// Returns a future that sends the request and resolves to the reply.
+	///
+	/// The request is not sent until the future is first polled, at which
+	/// point the timeout starts. Dropping the future abandons the request.
+	///
+	/// # Errors
+	///
+	/// The future resolves to an error in the same cases as
+	/// [`ReqClient::call`].
+	///
+	///
+	/// [`ReqClient::call`]: struct.ReqClient.html#method.call
+	pub fn call_fut<M: Into<Message>>(&self, msg: M, timeout: Option<Duration>) -> CallFuture
+	{
+		CallFuture {
+			state: Call::Start(self.socket.clone(), msg.into()),
+			timeout,
+			resend: self.resend,
+		}
+	}
+
+	/// Returns the socket on which requests are made.
+	pub fn socket(&self) -> &Socket { &self.socket }
+}
+
+/// A future that resolves to the reply of a [`ReqClient`] call.
+///
+///
+/// [`ReqClient`]: struct.ReqClient.html
+#[derive(Debug)]
+#[must_use = "the request is not sent until the future is polled"]
+pub struct CallFuture
+{
+	/// The current step of the call.
+	state: Call,
+
+	/// The timeout of the entire call.
+	timeout: Option<Duration>,
+
+	/// The resend time to set on the context of the call, if any.
+	#[allow(clippy::option_option)] // `None` is a valid resend time
+	resend: Option<Option<Duration>>,
+}
+
+impl Future for CallFuture
+{
+	type Output = Result<Message>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Self::Output>
+	{
+		let this = self.get_mut();
+		loop {
+			match mem::replace(&mut this.state, Call::Done) {
+				Call::Start(socket, msg) => {
+					let ctx = match Context::new(&socket) {
+						Ok(c) => c,
+						Err(e) => return Poll::Ready(Err(e)),
+					};
+
+					if let Some(resend) = this.resend {
+						if let Err(e) = ctx.set_opt::<ResendTime>(resend) {
+							return Poll::Ready(Err(e));
+						}
+					}
+
+					let deadline = this.timeout.map(|t| Instant::now() + t);
+					let fut = ctx.send_fut(msg).with_timeout(this.timeout);
+					this.state = Call::Sending { ctx, fut, deadline };
+				},
+				Call::Sending { ctx, mut fut, deadline } => match Pin::new(&mut fut).poll(cx) {
+					Poll::Ready(Ok(())) => {
+						// Whatever time the send took comes out of the time to wait for the reply.
+						let remaining =
+							deadline.map(|d| d.saturating_duration_since(Instant::now()));
+
+						this.state = Call::Receiving(ctx.recv_fut().with_timeout(remaining));
+					},
+					Poll::Ready(Err((_, e))) => return Poll::Ready(Err(e)),
+					Poll::Pending => {
+						this.state = Call::Sending { ctx, fut, deadline };
+						return Poll::Pending;
+					},
+				},
+				Call::Receiving(mut fut) => {
+					let poll = Pin::new(&mut fut).poll(cx);
+					if poll.is_pending() {
+						this.state = Call::Receiving(fut);
+					}
+					return poll;
+				},
+				Call::Done => panic!("CallFuture polled after completion"),
+			}
+		}
+	}
+}
+
+/// The steps of a `ReqClient` call.
+#[derive(Debug)]
+enum Call
+{
+	/// The call has not yet started.
+	Start(Socket, Message),
+
+	/// The request is being sent.
+	Sending
+	{
+		/// The context used for the call.
+		ctx: Context,
+
+		/// The send operation.
+		fut: SendFuture,
+
+		/// The time at which the call times out.
+		deadline: Option<Instant>,
+	},
+
+	/// Waiting on the reply, which also keeps the context open.
+	Receiving(RecvFuture),
+
+	/// The call has completed.
+	Done,
+}
+
+/// A user-provided request handler.
+enum Handler
+{
+	/// A handler that produces the reply immediately.
+	Sync(Box<dyn Fn(Message) -> Message + Send + Sync + 'static>),
+
+	/// A handler that produces a future that resolves to the reply.
+	Async(Box<dyn Fn(Message) -> HandlerFuture + Send + Sync + 'static>),
+}
+
+/// The state shared by all of the workers of a server.
+struct Shared
+{
+	/// The request handler.
+	handler: Handler,
+
+	/// The amount of time a handler has to produce a reply.
+	timeout: Option<Duration>,
+
+	/// Whether or not the server is shutting down.
+	stopping: AtomicBool,
+
+	/// The number of workers that have not yet stopped.
+	active: Mutex<usize>,
+
+	/// Signaled whenever a worker stops.
+	finished: Condvar,
+}
+
+/// The current state of a worker.
+enum State
+{
+	/// Waiting on a request to arrive.
+	Receiving,
+
+	/// Waiting on an asynchronous handler to produce the reply.
+	Handling
+	{
+		/// The handler's future, if it is not currently being polled.
+		fut: Option<HandlerFuture>,
+
+		/// Whether the future was woken while it was being polled.
+		notified: bool,
+
+		/// Whether the request timed out while the future was being polled.
+		expired: bool,
+	},
+
+	/// The reply is ready but the request timer needs to be canceled first.
+	Replying(Message),
+
+	/// Waiting on the reply to be sent.
+	Sending,
+
+	/// No longer doing anything.
+	Stopped,
+}
+
+/// A single request/reply loop.
+struct Worker
+{
+	/// The context on which requests are received.
+	ctx: Context,
+
+	/// The current state of the worker.
+	state: Mutex<State>,
+
+	/// The state shared by all workers of the server.
+	shared: Arc<Shared>,
+}
+impl Worker
+{
+	/// Advances the worker whenever its AIO completes.
+	fn callback(worker: &Arc<Worker>, aio: Aio, res: AioResult)
+	{
+		match res {
+			AioResult::Recv(Ok(msg)) => Worker::handle(worker, aio, msg),
+			AioResult::Recv(Err(Error::Closed | Error::Canceled)) => {
+				*worker.state.lock().unwrap() = State::Stopped;
+				worker.finish();
+			},
+			AioResult::Recv(Err(e)) => {
+				warn!("Failed to receive request: {e}");
+				worker.receive(&aio);
+			},
+			AioResult::Send(res) => {
+				if let Err((_, e)) = res {
+					warn!("Failed to send reply: {e}");
+				}
+				worker.receive(&aio);
+			},
+			AioResult::Sleep(_) => worker.expire(&aio),
+		}
+	}
+
+	/// Passes a received request to the handler.
+	fn handle(worker: &Arc<Worker>, aio: Aio, msg: Message)
+	{
+		match &worker.shared.handler {
+			Handler::Sync(f) => {
+				let start = Instant::now();
+				let reply = f(msg);
+
+				// A late reply is dropped, just as the future of an asynchronous handler would be.
+				match worker.shared.timeout {
+					Some(dur) if start.elapsed() > dur => worker.receive(&aio),
+					_ => worker.send(&aio, reply),
+				}
+			},
+			Handler::Async(f) => {
+				let fut = f(msg);
+				*worker.state.lock().unwrap() =
+					State::Handling { fut: Some(fut), notified: false, expired: false };
+
+				if let Some(dur) = worker.shared.timeout {
+					aio.sleep(dur).expect("Worker AIO should be idle while handling a request");
+				}
+
+				Task::run(&Arc::new(Task { worker: Arc::clone(worker), aio }));
+			},
+		}
+	}
+
+	/// Starts receiving the next request, unless the server is stopping.
+	fn receive(&self, aio: &Aio)
+	{
+		let mut state = self.state.lock().unwrap();
+		if self.shared.stopping.load(Ordering::SeqCst) {
+			*state = State::Stopped;
+			drop(state);
+			self.finish();
+			return;
+		}
+
+		// The receive has to be started while the lock is held so that `RepServer::stop`
+		// is guaranteed to cancel it.
+		*state = State::Receiving;
+		if let Err(e) = self.ctx.recv(aio) {
+			error!("Failed to start receiving a request: {e}");
+		}
+		drop(state);
+	}
+
+	/// Starts sending the reply.
+	fn send(&self, aio: &Aio, reply: Message)
+	{
+		*self.state.lock().unwrap() = State::Sending;
+		if let Err((_, e)) = self.ctx.send(aio, reply) {
+			error!("Failed to start sending a reply: {e}");
+		}
+	}
+
+	/// Handles the completion of the request timer.
+	fn expire(&self, aio: &Aio)
+	{
+		let mut state = self.state.lock().unwrap();
+		match mem::replace(&mut *state, State::Sending) {
+			// The handler finished first and canceled the timer.
+			State::Replying(reply) => {
+				drop(state);
+				self.send(aio, reply);
+			},
+
+			// The handler is taking too long. The future is dropped without the lock
+			// being held, as dropping it may wake it.
+			State::Handling { fut: Some(fut), .. } => {
+				drop(state);
+				drop(fut);
+				self.receive(aio);
+			},
+
+			// Someone is polling the future right now, so let them clean up.
+			State::Handling { fut: None, notified, .. } => {
+				*state = State::Handling { fut: None, notified, expired: true };
+				drop(state);
+			},
+
+			s => unreachable!("Request timer expired in an unexpected state ({:?})", s),
+		}
+	}
+
+	/// Marks the worker as being stopped.
+	fn finish(&self)
+	{
+		*self.shared.active.lock().unwrap() -= 1;
+		self.shared.finished.notify_all();
+	}
+}
+
+impl State
+{
+	/// Takes the handler future so that it can be polled.
+	///
+	/// If the future is not there, somebody else is currently polling it and
+	/// they are told that it was woken again.
+	fn take_future(&mut self) -> Option<HandlerFuture>
+	{
+		if let State::Handling { fut, notified, .. } = self {
+			let fut = fut.take();
+			*notified = fut.is_none();
+			fut
+		}
+		else {
+			None
+		}
+	}
+}
+
+impl fmt::Debug for State
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		match self {
+			State::Receiving => write!(f, "Receiving"),
+			State::Handling { .. } => write!(f, "Handling"),
+			State::Replying(_) => write!(f, "Replying"),
+			State::Sending => write!(f, "Sending"),
+			State::Stopped => write!(f, "Stopped"),
+		}
+	}
+}
+
+/// A handler future along with everything needed to deliver its reply.
+///
+/// This is what the wakers given to handler futures point to. Waking one
+/// polls the future on the current thread.
+struct Task
+{
+	/// The worker that is handling the request.
+	worker: Arc<Worker>,
+
+	/// The AIO object for the worker.
+	aio: Aio,
+}
+impl Task
+{
+	/// Polls the handler future until it is either pending or complete.
+	#[allow(clippy::significant_drop_tightening)] // The lock orders the state change with the AIO
+	fn run(task: &Arc<Task>)
+	{
+		let worker = &task.worker;
+		loop {
+			// The future is taken out of the worker so that it can be polled without the
+			// lock being held.
+			let fut = worker.state.lock().unwrap().take_future();
+			let Some(mut fut) = fut
+			else {
+				return;
+			};
+
+			let waker = Waker::from(Arc::clone(task));
+			let poll = fut.as_mut().poll(&mut TaskContext::from_waker(&waker));
+
+			let mut state = worker.state.lock().unwrap();
+			let (notified, expired) = match &*state {
+				State::Handling { notified, expired, .. } => (*notified, *expired),
+				s => unreachable!("Handler future was polled in an unexpected state ({:?})", s),
+			};
+
+			if expired {
+				drop(state);
+				drop(fut);
+				worker.receive(&task.aio);
+				return;
+			}
+
+			match poll {
+				// If there is a timer running, the reply has to wait for it to be canceled
+				// before the AIO can be used to send it.
+				Poll::Ready(reply) if worker.shared.timeout.is_some() => {
+					*state = State::Replying(reply);
+					task.aio.cancel();
+					return;
+				},
+				Poll::Ready(reply) => {
+					drop(state);
+					worker.send(&task.aio, reply);
+					return;
+				},
+				Poll::Pending => {
+					*state =
+						State::Handling { fut: Some(fut), notified: false, expired: false };
+					if !notified {
+						return;
+					}
+				},
+			}
+		}
+	}
+}
+
+impl Wake for Task
+{
+	fn wake(self: Arc<Self>) { Task::run(&self) }
+
+	fn wake_by_ref(self: &Arc<Self>) { Task::run(self) }
+}
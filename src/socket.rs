@@ -1,5 +1,6 @@
 use std::{
 	cmp::{Eq, Ordering, PartialEq, PartialOrd},
+	collections::BTreeMap,
 	convert::TryFrom,
 	error,
 	ffi::CString,
@@ -8,18 +9,39 @@ use std::{
 	num::NonZeroU32,
 	os::raw::{c_int, c_void},
 	ptr,
-	sync::{Arc, RwLock},
+	sync::{
+		atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering},
+		Arc, RwLock,
+	},
 };
 
 use crate::{
 	aio::Aio,
 	error::{Error, Result, SendResult},
+	future::{RecvFuture, SendFuture, Target},
 	message::Message,
-	pipe::{Pipe, PipeEvent},
+	pipe::{Pipe, PipeEvent, PipeInfo},
 	protocol::Protocol,
+	stats::{self, Stats},
 	util::{abort_unwind, validate_ptr},
 };
 
+#[cfg(feature = "serde")]
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(feature = "serde")]
+use crate::codec::{Codec, CodecError};
+
+mod events;
+pub use self::events::{PipeEvents, PipeSubscription};
+use self::events::{Queue, QueueSender, Subscriber};
+
+mod typed;
+pub use self::typed::{
+	Bus0Socket, Pair0Socket, Pair1Socket, Pub0Socket, Pull0Socket, Push0Socket, Rep0Socket,
+	Req0Socket, Respondent0Socket, Sub0Socket, Surveyor0Socket,
+};
+
 type PipeNotifyFn = dyn Fn(Pipe, PipeEvent) + Send + Sync + 'static;
 
 /// An NNG socket.
@@ -74,7 +96,15 @@ impl Socket
 		};
 
 		rv2res!(rv, Socket {
-			inner: Arc::new(Inner { handle: socket, pipe_notify: RwLock::new(None) }),
+			inner: Arc::new(Inner {
+				handle:      socket,
+				protocol:    t,
+				pipe_notify: RwLock::new(None),
+				pipes:       RwLock::new(None),
+				subscribers: RwLock::new(Vec::new()),
+				next_id:     AtomicUsize::new(0),
+				closed:      AtomicBool::new(false),
+			}),
 		})
 	}
 
@@ -398,11 +428,94 @@ impl Socket
 		aio.send_socket(self, msg)
 	}
 
+	/// Returns a future that resolves to the next message received on the socket.
+	///
+	/// The receive operation does not start until the future is first polled.
+	/// Dropping the future before it completes cancels the receive.
+	///
+	/// # Errors
+	///
+	/// The future resolves to an error in the same cases as [`Socket::recv`].
+	///
+	///
+	/// [`Socket::recv`]: struct.Socket.html#method.recv
+	pub fn recv_fut(&self) -> RecvFuture { RecvFuture::new(Target::Socket(self.clone())) }
+
+	/// Returns a future that resolves once the message has been sent on the socket.
+	///
+	/// The send operation does not start until the future is first polled.
+	/// Dropping the future before it completes cancels the send.
+	///
+	/// # Errors
+	///
+	/// The future resolves to an error in the same cases as [`Socket::send`].
+	///
+	///
+	/// [`Socket::send`]: struct.Socket.html#method.send
+	pub fn send_fut<M: Into<Message>>(&self, msg: M) -> SendFuture
+	{
+		SendFuture::new(Target::Socket(self.clone()), msg.into())
+	}
+
+	/// Serializes the value and sends it on the socket.
+	///
+	/// See [`Socket::send`] for the semantics of sending. If the message
+	/// cannot be sent, it is dropped.
+	///
+	/// This function is only available when the `serde` feature is enabled.
+	///
+	/// # Errors
+	///
+	/// * [`CodecError::Encode`]: The value could not be serialized.
+	/// * [`CodecError::Nng`]: The message could not be sent, for the reasons
+	///   listed on [`Socket::send`].
+	///
+	///
+	/// [`CodecError::Encode`]: codec/enum.CodecError.html#variant.Encode
+	/// [`CodecError::Nng`]: codec/enum.CodecError.html#variant.Nng
+	/// [`Socket::send`]: struct.Socket.html#method.send
+	#[cfg(feature = "serde")]
+	pub fn send_typed<T, C>(&self, value: &T, codec: C) -> std::result::Result<(), CodecError>
+	where
+		T: Serialize + ?Sized,
+		C: Codec,
+	{
+		let msg = Message::encode(value, codec)?;
+		self.send(msg).map_err(|(_, e)| CodecError::Nng(e))
+	}
+
+	/// Receives a message from the socket and deserializes its body.
+	///
+	/// See [`Socket::recv`] for the semantics of receiving.
+	///
+	/// This function is only available when the `serde` feature is enabled.
+	///
+	/// # Errors
+	///
+	/// * [`CodecError::Decode`]: The message body is not a valid value.
+	/// * [`CodecError::Nng`]: No message could be received, for the reasons
+	///   listed on [`Socket::recv`].
+	///
+	///
+	/// [`CodecError::Decode`]: codec/enum.CodecError.html#variant.Decode
+	/// [`CodecError::Nng`]: codec/enum.CodecError.html#variant.Nng
+	/// [`Socket::recv`]: struct.Socket.html#method.recv
+	#[cfg(feature = "serde")]
+	pub fn recv_typed<T: DeserializeOwned, C: Codec>(
+		&self,
+		codec: C,
+	) -> std::result::Result<T, CodecError>
+	{
+		self.recv()?.decode(codec)
+	}
+
 	/// Register a callback function to be called whenever a pipe event occurs
 	/// on the socket.
 	///
 	/// Only a single callback function can be supplied at a time. Registering a
-	/// new callback implicitly unregisters any previously registered.
+	/// new callback implicitly unregisters any previously registered. Use
+	/// [`subscribe_pipe_events`] or [`pipe_events`] to observe the events from
+	/// more than one place.
 	///
 	/// # Errors
 	///
@@ -416,6 +529,10 @@ impl Socket
 	/// produce the abort in order to keep things consistent. As such, the user
 	/// is responsible for either having a callback that never panics or
 	/// catching and handling the panic within the callback.
+	///
+	///
+	/// [`pipe_events`]: #method.pipe_events
+	/// [`subscribe_pipe_events`]: #method.subscribe_pipe_events
 	pub fn pipe_notify<F>(&self, callback: F) -> Result<()>
 	where
 		F: Fn(Pipe, PipeEvent) + Send + Sync + 'static,
@@ -426,6 +543,201 @@ impl Socket
 			*l = Some(Box::new(callback));
 		}
 
+		self.register_pipe_notify()
+	}
+
+	/// Registers an additional closure to be called whenever one of the pipe
+	/// events occurs on the socket.
+	///
+	/// Unlike [`pipe_notify`], any number of closures can be registered this
+	/// way and each one is only called for the events in `events`. They are
+	/// called after the closure set by [`pipe_notify`], in the order in which
+	/// they were registered. The closure is removed when the returned handle
+	/// is dropped.
+	///
+	/// # Errors
+	///
+	/// None specified.
+	///
+	/// # Panics
+	///
+	/// If the callback function panics, the program will log the panic if
+	/// possible and then abort, as with [`pipe_notify`].
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use nng::*;
+	/// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+	///
+	/// let server = Socket::new(Protocol::Rep0)?;
+	/// let (added, removed) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+	///
+	/// let a = Arc::clone(&added);
+	/// let _add = server.subscribe_pipe_events(&[PipeEvent::AddPost], move |_, _| {
+	///     a.fetch_add(1, Ordering::SeqCst);
+	/// })?;
+	/// let r = Arc::clone(&removed);
+	/// let _remove = server.subscribe_pipe_events(&[PipeEvent::RemovePost], move |_, _| {
+	///     r.fetch_add(1, Ordering::SeqCst);
+	/// })?;
+	///
+	/// server.listen("inproc://nng/subscribe_pipe_events")?;
+	/// let client = Socket::new(Protocol::Req0)?;
+	/// client.dial("inproc://nng/subscribe_pipe_events")?;
+	/// client.close();
+	/// # std::thread::sleep(std::time::Duration::from_millis(100));
+	///
+	/// assert_eq!(added.load(Ordering::SeqCst), 1);
+	/// assert_eq!(removed.load(Ordering::SeqCst), 1);
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`pipe_notify`]: #method.pipe_notify
+	#[allow(clippy::missing_panics_doc)]
+	pub fn subscribe_pipe_events<F>(
+		&self,
+		events: &[PipeEvent],
+		callback: F,
+	) -> Result<PipeSubscription>
+	where
+		F: Fn(Pipe, PipeEvent) + Send + Sync + 'static,
+	{
+		let id = self.inner.next_id.fetch_add(1, AtomicOrdering::Relaxed);
+		let subscriber = Subscriber { id, events: events.to_vec(), callback: Box::new(callback) };
+		self.inner.subscribers.write().unwrap().push(Arc::new(subscriber));
+
+		// Creating the handle first means the closure is removed again if this fails.
+		let subscription = PipeSubscription::new(id, Arc::downgrade(&self.inner));
+		self.register_pipe_notify().map(|()| subscription)
+	}
+
+	/// Returns the pipe events of the socket as a blocking iterator and, with
+	/// the `futures` feature, as an asynchronous stream.
+	///
+	/// Every event that occurs from this point on is queued until it is read,
+	/// so the events should be read regularly. This is independent of
+	/// [`pipe_notify`] and of any other [`PipeEvents`].
+	///
+	/// # Errors
+	///
+	/// None specified.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use nng::*;
+	///
+	/// let server = Socket::new(Protocol::Rep0)?;
+	/// let mut events = server.pipe_events()?.map(|(_, ev)| ev);
+	/// server.listen("inproc://nng/pipe_events")?;
+	///
+	/// let client = Socket::new(Protocol::Req0)?;
+	/// client.dial("inproc://nng/pipe_events")?;
+	/// assert_eq!(events.next(), Some(PipeEvent::AddPre));
+	/// assert_eq!(events.next(), Some(PipeEvent::AddPost));
+	///
+	/// client.close();
+	/// assert_eq!(events.next(), Some(PipeEvent::RemovePost));
+	///
+	/// drop(server);
+	/// assert_eq!(events.next(), None);
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`PipeEvents`]: struct.PipeEvents.html
+	/// [`pipe_notify`]: #method.pipe_notify
+	pub fn pipe_events(&self) -> Result<PipeEvents>
+	{
+		let queue = Arc::new(Queue::new());
+		let sender = QueueSender(Arc::clone(&queue));
+		let events = [PipeEvent::AddPre, PipeEvent::AddPost, PipeEvent::RemovePost];
+
+		self.subscribe_pipe_events(&events, move |pipe, ev| sender.send(pipe, ev))
+			.map(|subscription| PipeEvents::new(queue, subscription))
+	}
+
+	/// Starts keeping track of the pipes of the socket.
+	///
+	/// Once this has been called, the socket maintains a registry of its pipes
+	/// that is updated as they are added and removed, and which [`pipes`]
+	/// returns. NNG has no way of listing the pipes that already exist, so this
+	/// should be called before the socket dials or listens. Calling it again
+	/// has no effect. The registry is maintained independently of the callback
+	/// set by [`pipe_notify`].
+	///
+	/// # Errors
+	///
+	/// None specified.
+	///
+	///
+	/// [`pipe_notify`]: #method.pipe_notify
+	/// [`pipes`]: #method.pipes
+	#[allow(clippy::missing_panics_doc)]
+	pub fn track_pipes(&self) -> Result<()>
+	{
+		{
+			let mut l = self.inner.pipes.write().unwrap();
+			if l.is_none() {
+				*l = Some(BTreeMap::new());
+			}
+		}
+
+		self.register_pipe_notify()
+	}
+
+	/// Returns the pipes of the socket that are currently open.
+	///
+	/// The pipes are in the order in which they were created. Their addresses
+	/// and the dialer or listener that created them are captured when they are
+	/// added to the socket.
+	///
+	/// # Errors
+	///
+	/// * [`IncorrectState`]: The socket is not keeping track of its pipes, as
+	///   [`track_pipes`] has not been called.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use nng::*;
+	///
+	/// let server = Socket::new(Protocol::Rep0)?;
+	/// server.track_pipes()?;
+	/// server.listen("inproc://nng/pipes")?;
+	///
+	/// let client = Socket::new(Protocol::Req0)?;
+	/// client.dial("inproc://nng/pipes")?;
+	/// # std::thread::sleep(std::time::Duration::from_millis(100));
+	///
+	/// let pipes = server.pipes()?;
+	/// assert_eq!(pipes.len(), 1);
+	/// assert!(pipes[0].listener().is_some());
+	/// assert!(pipes[0].dialer().is_none());
+	///
+	/// client.close();
+	/// # std::thread::sleep(std::time::Duration::from_millis(100));
+	/// assert!(server.pipes()?.is_empty());
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`IncorrectState`]: enum.Error.html#variant.IncorrectState
+	/// [`track_pipes`]: #method.track_pipes
+	#[allow(clippy::missing_panics_doc)]
+	pub fn pipes(&self) -> Result<Vec<PipeInfo>>
+	{
+		match &*self.inner.pipes.read().unwrap() {
+			Some(pipes) => Ok(pipes.values().cloned().collect()),
+			None => Err(Error::IncorrectState),
+		}
+	}
+
+	/// Registers the trampoline for every pipe event of the socket.
+	fn register_pipe_notify(&self) -> Result<()>
+	{
 		// Because we're going to override the stored closure, we absolutely need to try
 		// and set the callback function for every single event. We cannot return
 		// early or we risk NNG trying to call into a closure that has been freed.
@@ -456,6 +768,30 @@ impl Socket
 			.fold(Ok(()), std::result::Result::and)
 	}
 
+	/// Returns the protocol with which the socket was opened.
+	pub fn protocol(&self) -> Protocol { self.inner.protocol }
+
+	/// Takes a snapshot of the statistics of this socket.
+	///
+	/// The root of the snapshot is the scope of the socket. See [`Stats`] for
+	/// more information.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The socket is not open.
+	/// * [`NotSupported`]: NNG was built without support for statistics.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`Stats`]: struct.Stats.html
+	pub fn stats(&self) -> Result<Stats>
+	{
+		stats::scoped("socket", unsafe { nng_sys::nng_socket_id(self.inner.handle) })
+	}
+
 	#[doc(hidden)]
 	#[deprecated(since = "1.0.0-rc.1", note = "Use `TryFrom` instead")]
 	pub fn into_raw(self) -> Option<RawSocket> { RawSocket::try_from(self).ok() }
@@ -480,6 +816,9 @@ impl Socket
 	/// Returns the underlying `nng_socket`.
 	pub(crate) fn handle(&self) -> nng_sys::nng_socket { self.inner.handle }
 
+	/// Returns whether the socket has been closed through any of its handles.
+	pub(crate) fn is_closed(&self) -> bool { self.inner.closed.load(AtomicOrdering::SeqCst) }
+
 	/// Trampoline function for calling the pipe event closure from C.
 	///
 	/// This is unsafe because you have to be absolutely positive that you
@@ -497,6 +836,14 @@ impl Socket
 			assert!(!arg.is_null(), "Null pointer passed as argument to trampoline");
 			let inner = &*(arg as *const _ as *const Inner);
 
+			if let Some(pipes) = &mut *inner.pipes.write().unwrap() {
+				match ev {
+					PipeEvent::AddPost => drop(pipes.insert(pipe, PipeInfo::new(pipe))),
+					PipeEvent::RemovePost => drop(pipes.remove(&pipe)),
+					_ => {},
+				}
+			}
+
 			// There are three alternatives to holding this lock during the callback:
 			//
 			// 1. Changing the `Box` to an `Arc` and cloning it.
@@ -514,6 +861,13 @@ impl Socket
 			if let Some(callback) = &*inner.pipe_notify.read().unwrap() {
 				(*callback)(pipe, ev)
 			}
+
+			// The subscribers are cloned so that a subscription can be dropped from within a
+			// callback without deadlocking.
+			let subscribers = inner.subscribers.read().unwrap().clone();
+			for sub in subscribers.iter().filter(|s| s.events.contains(&ev)) {
+				(sub.callback)(pipe, ev);
+			}
 		});
 	}
 }
@@ -570,6 +924,7 @@ expose_options!{
 	GETOPT_BOOL = nng_sys::nng_socket_get_bool;
 	GETOPT_INT = nng_sys::nng_socket_get_int;
 	GETOPT_MS = nng_sys::nng_socket_get_ms;
+	GETOPT_PTR = nng_sys::nng_socket_get_ptr;
 	GETOPT_SIZE = nng_sys::nng_socket_get_size;
 	GETOPT_SOCKADDR = nng_sys::nng_socket_get_addr;
 	GETOPT_STRING = nng_sys::nng_socket_get_string;
@@ -610,10 +965,140 @@ expose_options!{
 mod unix_impls
 {
 	use super::*;
-	use crate::options::{GetOpt, RecvFd, SendFd};
+	use std::task::{Context as TaskContext, Poll};
+
+	use crate::{
+		options::{GetOpt, Options, RecvFd, SendFd},
+		readiness::Readiness,
+	};
 
 	impl GetOpt<RecvFd> for Socket {}
 	impl GetOpt<SendFd> for Socket {}
+
+	impl Socket
+	{
+		/// Returns a handle that becomes readable when a message can be received.
+		///
+		/// See [`Readiness`] for more information.
+		///
+		/// # Errors
+		///
+		/// * [`Closed`]: The socket is not open.
+		/// * [`NotSupported`]: The protocol does not support receiving.
+		///
+		///
+		/// [`Closed`]: enum.Error.html#variant.Closed
+		/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+		/// [`Readiness`]: struct.Readiness.html
+		pub fn recv_readiness(&self) -> Result<Readiness>
+		{
+			self.get_opt::<RecvFd>().map(|fd| Readiness::new(fd, self.clone()))
+		}
+
+		/// Returns a handle that becomes readable when a message can be sent.
+		///
+		/// See [`Readiness`] for more information.
+		///
+		/// # Errors
+		///
+		/// * [`Closed`]: The socket is not open.
+		/// * [`NotSupported`]: The protocol does not support sending.
+		///
+		///
+		/// [`Closed`]: enum.Error.html#variant.Closed
+		/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+		/// [`Readiness`]: struct.Readiness.html
+		pub fn send_readiness(&self) -> Result<Readiness>
+		{
+			self.get_opt::<SendFd>().map(|fd| Readiness::new(fd, self.clone()))
+		}
+
+		/// Polls whether a message can be received without blocking.
+		///
+		/// Once this is ready, [`try_recv`] can receive the message without an
+		/// [`Aio`] or a blocked thread. See [`Readiness::poll_ready`] for how the
+		/// task is woken.
+		///
+		/// # Errors
+		///
+		/// * [`Closed`]: The socket is not open.
+		/// * [`NotSupported`]: The protocol does not support receiving.
+		///
+		/// # Panics
+		///
+		/// Panics if the thread that wakes the tasks can't be started.
+		///
+		/// ## Example
+		///
+		/// ```
+		/// use futures::{executor::block_on, future::poll_fn, ready};
+		/// use nng::*;
+		/// use std::{task::Poll, thread, time::Duration};
+		///
+		/// let server = Socket::new(Protocol::Pull0)?;
+		/// server.listen("inproc://nng/poll_recv_ready")?;
+		///
+		/// let client = Socket::new(Protocol::Push0)?;
+		/// client.dial("inproc://nng/poll_recv_ready")?;
+		/// thread::spawn(move || {
+		///     thread::sleep(Duration::from_millis(50));
+		///     client.send(&b"hello"[..]).unwrap();
+		/// });
+		///
+		/// let msg = block_on(poll_fn(|cx| loop {
+		///     ready!(server.poll_recv_ready(cx))?;
+		///     match server.try_recv() {
+		///         Err(Error::TryAgain) => continue,
+		///         res => return Poll::Ready(res),
+		///     }
+		/// }))?;
+		/// assert_eq!(&msg[..], b"hello");
+		/// # Ok::<(), nng::Error>(())
+		/// ```
+		///
+		///
+		/// [`Aio`]: struct.Aio.html
+		/// [`Closed`]: enum.Error.html#variant.Closed
+		/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+		/// [`Readiness::poll_ready`]: struct.Readiness.html#method.poll_ready
+		/// [`try_recv`]: #method.try_recv
+		pub fn poll_recv_ready(&self, cx: &mut TaskContext) -> Poll<Result<()>>
+		{
+			match self.recv_readiness() {
+				Ok(r) => r.poll_ready(cx).map(Ok),
+				Err(e) => Poll::Ready(Err(e)),
+			}
+		}
+
+		/// Polls whether a message can be sent without blocking.
+		///
+		/// Once this is ready, [`try_send`] can send a message without an
+		/// [`Aio`] or a blocked thread. See [`Readiness::poll_ready`] for how the
+		/// task is woken.
+		///
+		/// # Errors
+		///
+		/// * [`Closed`]: The socket is not open.
+		/// * [`NotSupported`]: The protocol does not support sending.
+		///
+		/// # Panics
+		///
+		/// Panics if the thread that wakes the tasks can't be started.
+		///
+		///
+		/// [`Aio`]: struct.Aio.html
+		/// [`Closed`]: enum.Error.html#variant.Closed
+		/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+		/// [`Readiness::poll_ready`]: struct.Readiness.html#method.poll_ready
+		/// [`try_send`]: #method.try_send
+		pub fn poll_send_ready(&self, cx: &mut TaskContext) -> Poll<Result<()>>
+		{
+			match self.send_readiness() {
+				Ok(r) => r.poll_ready(cx).map(Ok),
+				Err(e) => Poll::Ready(Err(e)),
+			}
+		}
+	}
 }
 
 /// A wrapper type around the underlying `nng_socket`.
@@ -625,13 +1110,32 @@ struct Inner
 	/// Handle to the underlying NNG socket.
 	handle: nng_sys::nng_socket,
 
+	/// The protocol the socket was opened with.
+	protocol: Protocol,
+
 	/// The current pipe event callback.
 	pipe_notify: RwLock<Option<Box<PipeNotifyFn>>>,
+
+	/// The open pipes of the socket, if they are being tracked.
+	pipes: RwLock<Option<BTreeMap<Pipe, PipeInfo>>>,
+
+	/// The additional closures subscribed to pipe events.
+	subscribers: RwLock<Vec<Arc<Subscriber>>>,
+
+	/// The identifier of the next subscriber.
+	next_id: AtomicUsize,
+
+	/// Whether the socket has been closed.
+	closed: AtomicBool,
 }
 impl Inner
 {
 	fn close(&self)
 	{
+		// The flag is set first so that it is visible to anyone who sees the readiness
+		// descriptors of the socket become invalid.
+		self.closed.store(true, AtomicOrdering::SeqCst);
+
 		// Closing a socket should only ever return success or ECLOSED and both
 		// of those mean we have nothing to drop. However, just to be sane
 		// about it all, we'll warn the user if we see something odd. If that
@@ -642,6 +1146,11 @@ impl Inner
 			"Unexpected error code while closing socket ({})",
 			rv
 		);
+
+		// This also closes the readiness descriptors, which the reactor can't tell apart from
+		// reused ones, so the tasks waiting on them are woken and forgotten here.
+		#[cfg(unix)]
+		crate::readiness::forget(unsafe { nng_sys::nng_socket_id(self.handle) });
 	}
 }
 
@@ -651,7 +1160,12 @@ impl fmt::Debug for Inner
 	{
 		f.debug_struct("Inner")
 			.field("handle", &self.handle)
+			.field("protocol", &self.protocol)
 			.field("pipe_notify", &self.pipe_notify.read().unwrap().is_some())
+			.field("pipes", &self.pipes.read().unwrap().as_ref().map(BTreeMap::len))
+			.field("subscribers", &self.subscribers.read().unwrap().len())
+			.field("next_id", &self.next_id)
+			.field("closed", &self.closed)
 			.finish()
 	}
 }
@@ -722,8 +1236,17 @@ impl RawSocket
 			return Err(Error::from(e));
 		}
 
-		let socket =
-			Socket { inner: Arc::new(Inner { handle: socket, pipe_notify: RwLock::new(None) }) };
+		let socket = Socket {
+			inner: Arc::new(Inner {
+				handle:      socket,
+				protocol:    t,
+				pipe_notify: RwLock::new(None),
+				pipes:       RwLock::new(None),
+				subscribers: RwLock::new(Vec::new()),
+				next_id:     AtomicUsize::new(0),
+				closed:      AtomicBool::new(false),
+			}),
+		};
 
 		Ok(RawSocket { socket, _priv: () })
 	}
@@ -0,0 +1,227 @@
+use std::{
+	collections::VecDeque,
+	sync::{Arc, Condvar, Mutex, Weak},
+	task::Waker,
+};
+
+#[cfg(feature = "futures")]
+use std::{
+	pin::Pin,
+	task::{Context as TaskContext, Poll},
+};
+
+#[cfg(feature = "futures")]
+use futures_core::stream::{FusedStream, Stream};
+
+use super::{Inner, PipeNotifyFn};
+use crate::pipe::{Pipe, PipeEvent};
+
+/// A registration of a closure for the pipe events of a socket.
+///
+/// The closure is removed from the socket when this handle is dropped. It may
+/// still be running at that point if an event was already being delivered on
+/// another thread, but it is not called for any later event. The handle does
+/// not keep the socket open.
+///
+/// See [`Socket::subscribe_pipe_events`] for more information.
+///
+///
+/// [`Socket::subscribe_pipe_events`]: struct.Socket.html#method.subscribe_pipe_events
+#[derive(Debug)]
+#[must_use = "the closure is removed from the socket when the subscription is dropped"]
+pub struct PipeSubscription
+{
+	/// The identifier of the subscriber within the socket.
+	id: usize,
+
+	/// The socket that the subscriber belongs to.
+	inner: Weak<Inner>,
+}
+impl PipeSubscription
+{
+	/// Creates a handle for the subscriber with the identifier.
+	pub(super) fn new(id: usize, inner: Weak<Inner>) -> Self { PipeSubscription { id, inner } }
+}
+
+impl Drop for PipeSubscription
+{
+	fn drop(&mut self)
+	{
+		if let Some(inner) = self.inner.upgrade() {
+			inner.subscribers.write().unwrap().retain(|s| s.id != self.id);
+		}
+	}
+}
+
+/// A closure that has subscribed to some of the pipe events of a socket.
+pub(super) struct Subscriber
+{
+	/// The identifier of the subscriber within the socket.
+	pub(super) id: usize,
+
+	/// The events that the closure is called for.
+	pub(super) events: Vec<PipeEvent>,
+
+	/// The closure to call.
+	pub(super) callback: Box<PipeNotifyFn>,
+}
+
+/// The pipe events of a socket, as a blocking iterator or an asynchronous
+/// stream.
+///
+/// Events are queued from the moment this is created until it is dropped,
+/// whether or not they are being read, and both the iterator and the stream
+/// end once the socket has been dropped and the queued events have been read.
+/// As both `Iterator` and `StreamExt` have a `next` method, the asynchronous
+/// one has to be called as `StreamExt::next(&mut events)` when both traits are
+/// in scope.
+///
+/// See [`Socket::pipe_events`] for more information.
+///
+///
+/// [`Socket::pipe_events`]: struct.Socket.html#method.pipe_events
+#[derive(Debug)]
+pub struct PipeEvents
+{
+	/// The queue that the subscriber pushes events onto.
+	queue: Arc<Queue>,
+
+	/// The subscription that feeds the queue.
+	_subscription: PipeSubscription,
+}
+impl PipeEvents
+{
+	/// Creates a new event stream from the subscription that feeds the queue.
+	pub(super) fn new(queue: Arc<Queue>, subscription: PipeSubscription) -> Self
+	{
+		PipeEvents { queue, _subscription: subscription }
+	}
+
+	/// Returns the next event, if there is one, without blocking.
+	///
+	/// Returns `None` if no event is queued, even if the socket still exists.
+	#[allow(clippy::missing_panics_doc)]
+	pub fn try_next(&self) -> Option<(Pipe, PipeEvent)>
+	{
+		self.queue.state.lock().unwrap().events.pop_front()
+	}
+}
+
+impl Iterator for PipeEvents
+{
+	type Item = (Pipe, PipeEvent);
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		let mut state = self.queue.state.lock().unwrap();
+		loop {
+			if let Some(ev) = state.events.pop_front() {
+				return Some(ev);
+			}
+
+			if state.closed {
+				return None;
+			}
+
+			state = self.queue.ready.wait(state).unwrap();
+		}
+	}
+}
+
+#[cfg(feature = "futures")]
+impl Stream for PipeEvents
+{
+	type Item = (Pipe, PipeEvent);
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Option<Self::Item>>
+	{
+		let mut state = self.queue.state.lock().unwrap();
+		if let Some(ev) = state.events.pop_front() {
+			return Poll::Ready(Some(ev));
+		}
+
+		if state.closed {
+			return Poll::Ready(None);
+		}
+
+		state.waker = Some(cx.waker().clone());
+		Poll::Pending
+	}
+}
+
+#[cfg(feature = "futures")]
+impl FusedStream for PipeEvents
+{
+	fn is_terminated(&self) -> bool
+	{
+		let state = self.queue.state.lock().unwrap();
+		state.closed && state.events.is_empty()
+	}
+}
+
+/// The events that have not yet been read from a `PipeEvents`.
+#[derive(Debug)]
+pub(super) struct Queue
+{
+	/// The queued events and the state of the socket.
+	state: Mutex<QueueState>,
+
+	/// Signals blocked readers that an event was queued or the socket is gone.
+	ready: Condvar,
+}
+impl Queue
+{
+	/// Creates a new, empty queue.
+	pub(super) fn new() -> Self
+	{
+		let state = QueueState { events: VecDeque::new(), closed: false, waker: None };
+		Queue { state: Mutex::new(state), ready: Condvar::new() }
+	}
+}
+
+/// The contents of a `Queue`.
+#[derive(Debug)]
+struct QueueState
+{
+	/// The events that have not yet been read.
+	events: VecDeque<(Pipe, PipeEvent)>,
+
+	/// Whether the socket is gone and so no more events will be queued.
+	closed: bool,
+
+	/// The task waiting for the next event.
+	waker: Option<Waker>,
+}
+
+/// The side of a `Queue` that is owned by the subscriber closure.
+///
+/// The closure is dropped along with the socket, which closes the queue.
+#[derive(Debug)]
+pub(super) struct QueueSender(pub(super) Arc<Queue>);
+impl QueueSender
+{
+	/// Queues the event and wakes any reader.
+	pub(super) fn send(&self, pipe: Pipe, ev: PipeEvent)
+	{
+		self.update(|state| state.events.push_back((pipe, ev)));
+	}
+
+	/// Changes the state of the queue and then wakes any reader.
+	fn update<F: FnOnce(&mut QueueState)>(&self, f: F)
+	{
+		let mut state = self.0.state.lock().unwrap();
+		f(&mut state);
+		let waker = state.waker.take();
+		drop(state);
+
+		self.0.ready.notify_all();
+		if let Some(w) = waker {
+			w.wake();
+		}
+	}
+}
+
+impl Drop for QueueSender
+{
+	fn drop(&mut self) { self.update(|state| state.closed = true); }
+}
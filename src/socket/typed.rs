@@ -0,0 +1,562 @@
+use std::convert::TryFrom;
+
+use super::{PipeEvents, PipeSubscription, Socket};
+use crate::{
+	aio::Aio,
+	error::{Error, Result, SendResult},
+	future::{RecvFuture, SendFuture},
+	message::Message,
+	options::{Options, Raw},
+	pipe::{Pipe, PipeEvent, PipeInfo},
+	protocol::Protocol,
+};
+
+/// Defines a socket type that is specific to the given protocol.
+macro_rules! typed_socket
+{
+	(
+		$(#[$attr:meta])*
+		$name:ident = $proto:ident [$($op:ident),*];
+		Gets -> [$($($getters:ident)::+),*];
+		Sets -> [$($($setters:ident)::+),*];
+	) => {
+		$(#[$attr])*
+		#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+		pub struct $name
+		{
+			/// The untyped socket.
+			socket: Socket,
+		}
+		impl $name
+		{
+			/// Creates a new socket that uses the protocol of this type.
+			///
+			/// # Errors
+			///
+			/// * [`NotSupported`]: Protocol is not enabled.
+			/// * [`OutOfMemory`]: Insufficient memory available.
+			///
+			///
+			/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+			/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+			pub fn new() -> Result<Self>
+			{
+				Socket::new(Protocol::$proto).map(|socket| $name { socket })
+			}
+
+			/// Initiates a remote connection to a listener.
+			///
+			/// See [`Socket::dial`] for more information.
+			///
+			/// # Errors
+			///
+			/// Fails in the same cases as [`Socket::dial`].
+			///
+			///
+			/// [`Socket::dial`]: struct.Socket.html#method.dial
+			pub fn dial(&self, url: &str) -> Result<()> { self.socket.dial(url) }
+
+			/// Asynchronously initiates a remote connection to a listener.
+			///
+			/// See [`Socket::dial_async`] for more information.
+			///
+			/// # Errors
+			///
+			/// Fails in the same cases as [`Socket::dial_async`].
+			///
+			///
+			/// [`Socket::dial_async`]: struct.Socket.html#method.dial_async
+			pub fn dial_async(&self, url: &str) -> Result<()> { self.socket.dial_async(url) }
+
+			/// Initiates and starts a listener on the specified address.
+			///
+			/// See [`Socket::listen`] for more information.
+			///
+			/// # Errors
+			///
+			/// Fails in the same cases as [`Socket::listen`].
+			///
+			///
+			/// [`Socket::listen`]: struct.Socket.html#method.listen
+			pub fn listen(&self, url: &str) -> Result<()> { self.socket.listen(url) }
+
+			/// Register a callback function to be called whenever a pipe event
+			/// occurs on the socket.
+			///
+			/// See [`Socket::pipe_notify`] for more information.
+			///
+			/// # Errors
+			///
+			/// None specified.
+			///
+			///
+			/// [`Socket::pipe_notify`]: struct.Socket.html#method.pipe_notify
+			pub fn pipe_notify<F>(&self, callback: F) -> Result<()>
+			where
+				F: Fn(Pipe, PipeEvent) + Send + Sync + 'static,
+			{
+				self.socket.pipe_notify(callback)
+			}
+
+			/// Registers an additional closure to be called whenever one of the
+			/// pipe events occurs on the socket.
+			///
+			/// See [`Socket::subscribe_pipe_events`] for more information.
+			///
+			/// # Errors
+			///
+			/// None specified.
+			///
+			///
+			/// [`Socket::subscribe_pipe_events`]: struct.Socket.html#method.subscribe_pipe_events
+			pub fn subscribe_pipe_events<F>(
+				&self,
+				events: &[PipeEvent],
+				callback: F,
+			) -> Result<PipeSubscription>
+			where
+				F: Fn(Pipe, PipeEvent) + Send + Sync + 'static,
+			{
+				self.socket.subscribe_pipe_events(events, callback)
+			}
+
+			/// Returns the pipe events of the socket as an iterator or stream.
+			///
+			/// See [`Socket::pipe_events`] for more information.
+			///
+			/// # Errors
+			///
+			/// None specified.
+			///
+			///
+			/// [`Socket::pipe_events`]: struct.Socket.html#method.pipe_events
+			pub fn pipe_events(&self) -> Result<PipeEvents> { self.socket.pipe_events() }
+
+			/// Starts keeping track of the pipes of the socket.
+			///
+			/// See [`Socket::track_pipes`] for more information.
+			///
+			/// # Errors
+			///
+			/// None specified.
+			///
+			///
+			/// [`Socket::track_pipes`]: struct.Socket.html#method.track_pipes
+			pub fn track_pipes(&self) -> Result<()> { self.socket.track_pipes() }
+
+			/// Returns the pipes of the socket that are currently open.
+			///
+			/// See [`Socket::pipes`] for more information.
+			///
+			/// # Errors
+			///
+			/// Fails in the same cases as [`Socket::pipes`].
+			///
+			///
+			/// [`Socket::pipes`]: struct.Socket.html#method.pipes
+			pub fn pipes(&self) -> Result<Vec<PipeInfo>> { self.socket.pipes() }
+
+			/// Close the underlying socket.
+			///
+			/// See [`Socket::close`] for more information.
+			///
+			///
+			/// [`Socket::close`]: struct.Socket.html#method.close
+			pub fn close(&self) { self.socket.close() }
+
+			/// Returns a reference to the untyped socket.
+			///
+			/// This can be used to create a [`Context`] or any other object that
+			/// works on an untyped socket.
+			///
+			///
+			/// [`Context`]: struct.Context.html
+			pub fn socket(&self) -> &Socket { &self.socket }
+
+			/// Converts this into the untyped socket.
+			pub fn into_socket(self) -> Socket { self.socket }
+
+			$(typed_socket!(@$op);)*
+		}
+
+		impl From<$name> for Socket
+		{
+			fn from(s: $name) -> Socket { s.socket }
+		}
+
+		impl TryFrom<Socket> for $name
+		{
+			type Error = Error;
+
+			/// Checks that the socket is a cooked socket of the matching protocol.
+			///
+			/// The conversion fails with [`NotSupported`] if it is not.
+			///
+			///
+			/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+			fn try_from(socket: Socket) -> Result<Self>
+			{
+				if socket.protocol() != Protocol::$proto || socket.get_opt::<Raw>()? {
+					return Err(Error::NotSupported);
+				}
+
+				Ok($name { socket })
+			}
+		}
+
+		$(typed_socket!(@fd $name $op);)*
+
+		#[rustfmt::skip]
+		expose_options!{
+			$name :: socket.inner.handle -> nng_sys::nng_socket;
+
+			GETOPT_BOOL = nng_sys::nng_socket_get_bool;
+			GETOPT_INT = nng_sys::nng_socket_get_int;
+			GETOPT_MS = nng_sys::nng_socket_get_ms;
+			GETOPT_PTR = nng_sys::nng_socket_get_ptr;
+			GETOPT_SIZE = nng_sys::nng_socket_get_size;
+			GETOPT_SOCKADDR = nng_sys::nng_socket_get_addr;
+			GETOPT_STRING = nng_sys::nng_socket_get_string;
+			GETOPT_UINT64 = nng_sys::nng_socket_get_uint64;
+
+			SETOPT = nng_sys::nng_socket_set;
+			SETOPT_BOOL = nng_sys::nng_socket_set_bool;
+			SETOPT_INT = nng_sys::nng_socket_set_int;
+			SETOPT_MS = nng_sys::nng_socket_set_ms;
+			SETOPT_PTR = nng_sys::nng_socket_set_ptr;
+			SETOPT_SIZE = nng_sys::nng_socket_set_size;
+			SETOPT_STRING = nng_sys::nng_socket_set_string;
+
+			Gets -> [SocketName $(, $($getters)::+)*];
+			Sets -> [ReconnectMinTime, ReconnectMaxTime,
+			         RecvMaxSize, SocketName,
+			         transport::tcp::NoDelay,
+			         transport::tcp::KeepAlive,
+			         transport::tls::CaFile,
+			         transport::tls::CertKeyFile,
+			         transport::websocket::RequestHeaders,
+			         transport::websocket::ResponseHeaders
+			         $(, $($setters)::+)*];
+		}
+	};
+
+	(@send) => {
+		/// Sends a message on the socket.
+		///
+		/// See [`Socket::send`] for more information.
+		///
+		/// # Errors
+		///
+		/// Fails in the same cases as [`Socket::send`].
+		///
+		///
+		/// [`Socket::send`]: struct.Socket.html#method.send
+		pub fn send<M: Into<Message>>(&self, msg: M) -> SendResult<()> { self.socket.send(msg) }
+
+		/// Attempts to send a message on the socket without blocking.
+		///
+		/// See [`Socket::try_send`] for more information.
+		///
+		/// # Errors
+		///
+		/// Fails in the same cases as [`Socket::try_send`].
+		///
+		///
+		/// [`Socket::try_send`]: struct.Socket.html#method.try_send
+		pub fn try_send<M: Into<Message>>(&self, msg: M) -> SendResult<()>
+		{
+			self.socket.try_send(msg)
+		}
+
+		/// Start a send operation on the given `Aio` and return immediately.
+		///
+		/// # Errors
+		///
+		/// * [`IncorrectState`]: The `Aio` already has a running operation.
+		///
+		///
+		/// [`IncorrectState`]: enum.Error.html#variant.IncorrectState
+		pub fn send_async<M: Into<Message>>(&self, aio: &Aio, msg: M) -> SendResult<()>
+		{
+			self.socket.send_async(aio, msg)
+		}
+
+		/// Returns a future that resolves once the message has been sent on the
+		/// socket.
+		///
+		/// See [`Socket::send_fut`] for more information.
+		///
+		///
+		/// [`Socket::send_fut`]: struct.Socket.html#method.send_fut
+		pub fn send_fut<M: Into<Message>>(&self, msg: M) -> SendFuture { self.socket.send_fut(msg) }
+	};
+
+	(@recv) => {
+		/// Receives a message from the socket.
+		///
+		/// See [`Socket::recv`] for more information.
+		///
+		/// # Errors
+		///
+		/// Fails in the same cases as [`Socket::recv`].
+		///
+		///
+		/// [`Socket::recv`]: struct.Socket.html#method.recv
+		pub fn recv(&self) -> Result<Message> { self.socket.recv() }
+
+		/// Attempts to receive a message from the socket without blocking.
+		///
+		/// See [`Socket::try_recv`] for more information.
+		///
+		/// # Errors
+		///
+		/// Fails in the same cases as [`Socket::try_recv`].
+		///
+		///
+		/// [`Socket::try_recv`]: struct.Socket.html#method.try_recv
+		pub fn try_recv(&self) -> Result<Message> { self.socket.try_recv() }
+
+		/// Start a receive operation using the given `Aio` and return
+		/// immediately.
+		///
+		/// # Errors
+		///
+		/// * [`IncorrectState`]: The `Aio` already has a running operation.
+		///
+		///
+		/// [`IncorrectState`]: enum.Error.html#variant.IncorrectState
+		pub fn recv_async(&self, aio: &Aio) -> Result<()> { self.socket.recv_async(aio) }
+
+		/// Returns a future that resolves to the next message received on the
+		/// socket.
+		///
+		/// See [`Socket::recv_fut`] for more information.
+		///
+		///
+		/// [`Socket::recv_fut`]: struct.Socket.html#method.recv_fut
+		pub fn recv_fut(&self) -> RecvFuture { self.socket.recv_fut() }
+	};
+
+	(@fd $name:ident send) => {
+		#[cfg(unix)]
+		impl crate::options::GetOpt<crate::options::SendFd> for $name {}
+	};
+
+	(@fd $name:ident recv) => {
+		#[cfg(unix)]
+		impl crate::options::GetOpt<crate::options::RecvFd> for $name {}
+	};
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _bus_ protocol.
+	///
+	/// See [`Protocol::Bus0`] for more information.
+	///
+	///
+	/// [`Protocol::Bus0`]: enum.Protocol.html#variant.Bus0
+	Bus0Socket = Bus0 [send, recv];
+	Gets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout];
+	Sets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout];
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _pair_ protocol.
+	///
+	/// See [`Protocol::Pair0`] for more information.
+	///
+	///
+	/// [`Protocol::Pair0`]: enum.Protocol.html#variant.Pair0
+	Pair0Socket = Pair0 [send, recv];
+	Gets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout];
+	Sets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout];
+}
+
+typed_socket! {
+	/// A socket that uses version 1 of the _pair_ protocol.
+	///
+	/// See [`Protocol::Pair1`] for more information.
+	///
+	///
+	/// [`Protocol::Pair1`]: enum.Protocol.html#variant.Pair1
+	Pair1Socket = Pair1 [send, recv];
+	Gets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl,
+			 protocol::pair::Polyamorous];
+	Sets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl,
+			 protocol::pair::Polyamorous];
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _pub_ protocol.
+	///
+	/// See [`Protocol::Pub0`] for more information.
+	///
+	/// Publishers are unable to receive messages, so the following is a compile
+	/// error:
+	///
+	/// ```compile_fail
+	/// use nng::*;
+	///
+	/// let socket = Pub0Socket::new().unwrap();
+	/// let msg = socket.recv().unwrap(); // Won't compile
+	/// ```
+	///
+	///
+	/// [`Protocol::Pub0`]: enum.Protocol.html#variant.Pub0
+	Pub0Socket = Pub0 [send];
+	Gets -> [SendBufferSize, SendTimeout];
+	Sets -> [SendBufferSize, SendTimeout];
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _pull_ protocol.
+	///
+	/// See [`Protocol::Pull0`] for more information.
+	///
+	///
+	/// [`Protocol::Pull0`]: enum.Protocol.html#variant.Pull0
+	Pull0Socket = Pull0 [recv];
+	Gets -> [RecvBufferSize, RecvTimeout];
+	Sets -> [RecvBufferSize, RecvTimeout];
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _push_ protocol.
+	///
+	/// See [`Protocol::Push0`] for more information.
+	///
+	///
+	/// [`Protocol::Push0`]: enum.Protocol.html#variant.Push0
+	Push0Socket = Push0 [send];
+	Gets -> [SendBufferSize, SendTimeout];
+	Sets -> [SendBufferSize, SendTimeout];
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _rep_ protocol.
+	///
+	/// See [`Protocol::Rep0`] for more information.
+	///
+	///
+	/// [`Protocol::Rep0`]: enum.Protocol.html#variant.Rep0
+	Rep0Socket = Rep0 [send, recv];
+	Gets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl];
+	Sets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl];
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _req_ protocol.
+	///
+	/// See [`Protocol::Req0`] for more information.
+	///
+	///
+	/// [`Protocol::Req0`]: enum.Protocol.html#variant.Req0
+	Req0Socket = Req0 [send, recv];
+	Gets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl,
+			 protocol::reqrep::ResendTime];
+	Sets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl,
+			 protocol::reqrep::ResendTime];
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _respondent_ protocol.
+	///
+	/// See [`Protocol::Respondent0`] for more information.
+	///
+	///
+	/// [`Protocol::Respondent0`]: enum.Protocol.html#variant.Respondent0
+	Respondent0Socket = Respondent0 [send, recv];
+	Gets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl];
+	Sets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl];
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _sub_ protocol.
+	///
+	/// A new subscriber receives nothing until it has subscribed to at least
+	/// one topic.
+	///
+	/// See [`Protocol::Sub0`] for more information.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use std::convert::TryFrom;
+	/// use nng::*;
+	///
+	/// let publisher = Pub0Socket::new()?;
+	/// publisher.listen("inproc://nng/typed/example")?;
+	///
+	/// let subscriber = Sub0Socket::new()?;
+	/// subscriber.subscribe(b"weather")?;
+	/// subscriber.dial("inproc://nng/typed/example")?;
+	/// # std::thread::sleep(std::time::Duration::from_millis(50));
+	///
+	/// publisher.send(&b"weather: sunny"[..]).map_err(Error::from)?;
+	/// let msg = subscriber.recv()?;
+	/// assert_eq!(&msg[..], b"weather: sunny");
+	///
+	/// // Typed sockets convert to and from the untyped `Socket`.
+	/// let socket: Socket = subscriber.into();
+	/// assert!(Pub0Socket::try_from(socket).is_err());
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`Protocol::Sub0`]: enum.Protocol.html#variant.Sub0
+	Sub0Socket = Sub0 [recv];
+	Gets -> [RecvBufferSize, RecvTimeout];
+	Sets -> [RecvBufferSize, RecvTimeout,
+			 protocol::pubsub::Subscribe,
+			 protocol::pubsub::Unsubscribe];
+}
+impl Sub0Socket
+{
+	/// Subscribes to messages that begin with the given topic.
+	///
+	/// An empty topic subscribes to all messages.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The socket is not open.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn subscribe(&self, topic: &[u8]) -> Result<()>
+	{
+		use crate::options::protocol::pubsub::Subscribe;
+		self.set_opt::<Subscribe>(topic.to_vec())
+	}
+
+	/// Removes a subscription that was previously added with `subscribe`.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The socket is not open.
+	/// * [`EntryNotFound`]: The socket was not subscribed to the topic.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`EntryNotFound`]: enum.Error.html#variant.EntryNotFound
+	pub fn unsubscribe(&self, topic: &[u8]) -> Result<()>
+	{
+		use crate::options::protocol::pubsub::Unsubscribe;
+		self.set_opt::<Unsubscribe>(topic.to_vec())
+	}
+}
+
+typed_socket! {
+	/// A socket that uses version 0 of the _surveyor_ protocol.
+	///
+	/// See [`Protocol::Surveyor0`] for more information.
+	///
+	///
+	/// [`Protocol::Surveyor0`]: enum.Protocol.html#variant.Surveyor0
+	Surveyor0Socket = Surveyor0 [send, recv];
+	Gets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl,
+			 protocol::survey::SurveyTime];
+	Sets -> [RecvBufferSize, RecvTimeout, SendBufferSize, SendTimeout, MaxTtl,
+			 protocol::survey::SurveyTime];
+}
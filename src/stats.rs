@@ -0,0 +1,397 @@
+use std::{
+	borrow::Cow,
+	ffi::{CStr, CString},
+	fmt,
+	iter::FusedIterator,
+	marker::PhantomData,
+	num::NonZeroU32,
+	os::raw::c_char,
+	ptr::{self, NonNull},
+	time::Duration,
+};
+
+use crate::error::{Error, Result};
+
+mod prometheus;
+pub use self::prometheus::PrometheusExporter;
+
+/// A snapshot of the statistics kept by NNG.
+///
+/// NNG keeps statistics for every socket, dialer, listener and pipe, which
+/// are arranged in a tree of [`Stat`] nodes. Scopes group the statistics of
+/// each object and contain the values themselves, such as the number of
+/// messages sent or the number of pipes that were rejected. A snapshot is a
+/// copy of the tree at the time it was taken and does not change afterwards.
+///
+/// The snapshot can either contain every statistic, as returned by
+/// [`Stats::get`], or only those of a single object, as returned by functions
+/// such as [`Socket::stats`]. Statistics are not part of the stable NNG API
+/// and so the names and the structure of the tree may change between NNG
+/// versions.
+///
+/// ## Example
+///
+/// ```
+/// use nng::*;
+///
+/// let socket = Socket::new(Protocol::Pair1)?;
+/// socket.listen("inproc://nng/stats/example")?;
+///
+/// let stats = socket.stats()?;
+/// assert_eq!(stats.root().name(), "socket");
+///
+/// let protocol = stats.find("protocol").unwrap();
+/// assert_eq!(protocol.value(), StatValue::String("pair1".into()));
+///
+/// let listeners = stats.find("listeners").unwrap();
+/// assert_eq!(listeners.value(), StatValue::Level(1));
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`Socket::stats`]: struct.Socket.html#method.stats
+/// [`Stat`]: struct.Stat.html
+/// [`Stats::get`]: struct.Stats.html#method.get
+pub struct Stats
+{
+	/// The root of the snapshot, which is what needs to be freed.
+	snapshot: NonNull<nng_sys::nng_stat>,
+
+	/// The node that this snapshot has been narrowed down to.
+	root: NonNull<nng_sys::nng_stat>,
+}
+impl Stats
+{
+	/// Takes a snapshot of all of the statistics.
+	///
+	/// # Errors
+	///
+	/// * [`NotSupported`]: NNG was built without support for statistics.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn get() -> Result<Self>
+	{
+		let mut snapshot = ptr::null_mut();
+		let rv = unsafe { nng_sys::nng_stats_get(&raw mut snapshot) };
+		if let Some(e) = NonZeroU32::new(rv as u32) {
+			return Err(Error::from(e));
+		}
+
+		let snapshot = NonNull::new(snapshot).ok_or(Error::OutOfMemory)?;
+		Ok(Stats { snapshot, root: snapshot })
+	}
+
+	/// Returns the root of the snapshot.
+	///
+	/// This is an unnamed scope for snapshots returned by [`Stats::get`] and
+	/// the scope of the object for snapshots returned by functions such as
+	/// [`Socket::stats`].
+	///
+	///
+	/// [`Socket::stats`]: struct.Socket.html#method.stats
+	/// [`Stats::get`]: struct.Stats.html#method.get
+	pub fn root(&self) -> Stat<'_> { Stat { ptr: self.root, _snapshot: PhantomData } }
+
+	/// Returns the first statistic with the given name, searching the root and
+	/// its descendants depth-first.
+	pub fn find(&self, name: &str) -> Option<Stat<'_>> { self.root().find(name) }
+}
+
+/// Narrows a new snapshot down to the scope with the given name and ID.
+///
+/// NNG identifies the scopes of sockets, dialers, listeners and pipes by an
+/// "id" statistic inside of the scope. If the scope isn't found, the object
+/// has been closed.
+#[allow(clippy::redundant_pub_crate)] // Makes it clear that this is not part of the public API
+pub(crate) fn scoped(name: &str, id: i32) -> Result<Stats>
+{
+	let mut stats = Stats::get()?;
+	let scope = find_scope(stats.root(), name, id as u64).ok_or(Error::Closed)?;
+
+	stats.root = scope.ptr;
+	Ok(stats)
+}
+
+/// Finds the scope with the given name and ID.
+///
+/// This does the same thing as `nng_stat_find_scope`, which only checks the
+/// name of nested scopes in current versions of NNG.
+fn find_scope<'a>(stat: Stat<'a>, name: &str, id: u64) -> Option<Stat<'a>>
+{
+	if stat.kind() == StatKind::Scope
+		&& stat.name() == name
+		&& stat.children().any(|c| c.name() == "id" && c.value() == StatValue::Id(id))
+	{
+		return Some(stat);
+	}
+
+	stat.children().find_map(|c| find_scope(c, name, id))
+}
+
+impl fmt::Debug for Stats
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		f.debug_struct("Stats").field("root", &self.root()).finish()
+	}
+}
+
+impl Drop for Stats
+{
+	fn drop(&mut self) { unsafe { nng_sys::nng_stats_free(self.snapshot.as_ptr()) } }
+}
+
+// The snapshot is a private copy of the statistics that is never modified by NNG, so it can be
+// freely read from and sent to other threads.
+unsafe impl Send for Stats {}
+unsafe impl Sync for Stats {}
+
+/// A single node in a snapshot of the statistics.
+///
+/// See [`Stats`] for more information.
+///
+///
+/// [`Stats`]: struct.Stats.html
+#[derive(Clone, Copy)]
+pub struct Stat<'a>
+{
+	/// The pointer to the node.
+	ptr: NonNull<nng_sys::nng_stat>,
+
+	/// The snapshot that owns the node.
+	_snapshot: PhantomData<&'a Stats>,
+}
+impl<'a> Stat<'a>
+{
+	/// Returns the name of the statistic.
+	pub fn name(self) -> Cow<'a, str> { unsafe { from_c_str(nng_sys::nng_stat_name(self.ptr())) } }
+
+	/// Returns the human-readable description of the statistic.
+	pub fn description(self) -> Cow<'a, str>
+	{
+		unsafe { from_c_str(nng_sys::nng_stat_desc(self.ptr())) }
+	}
+
+	/// Returns the kind of the statistic.
+	pub fn kind(self) -> StatKind
+	{
+		use nng_sys::nng_stat_type_enum::*;
+
+		let kind = unsafe { nng_sys::nng_stat_type(self.ptr()) };
+		match nng_sys::nng_stat_type_enum::try_convert_from(kind) {
+			Some(NNG_STAT_SCOPE) => StatKind::Scope,
+			Some(NNG_STAT_LEVEL) => StatKind::Level,
+			Some(NNG_STAT_COUNTER) => StatKind::Counter,
+			Some(NNG_STAT_STRING) => StatKind::String,
+			Some(NNG_STAT_BOOLEAN) => StatKind::Boolean,
+			Some(NNG_STAT_ID) => StatKind::Id,
+			_ => StatKind::Unknown(kind),
+		}
+	}
+
+	/// Returns the unit of the value of the statistic.
+	pub fn unit(self) -> StatUnit
+	{
+		use nng_sys::nng_unit_enum::*;
+
+		let unit = unsafe { nng_sys::nng_stat_unit(self.ptr()) };
+		match nng_sys::nng_unit_enum::try_convert_from(unit) {
+			Some(NNG_UNIT_NONE) => StatUnit::None,
+			Some(NNG_UNIT_BYTES) => StatUnit::Bytes,
+			Some(NNG_UNIT_MESSAGES) => StatUnit::Messages,
+			Some(NNG_UNIT_MILLIS) => StatUnit::Millis,
+			Some(NNG_UNIT_EVENTS) => StatUnit::Events,
+			_ => StatUnit::Unknown(unit),
+		}
+	}
+
+	/// Returns the value of the statistic.
+	pub fn value(self) -> StatValue<'a>
+	{
+		unsafe {
+			match self.kind() {
+				StatKind::Level => StatValue::Level(nng_sys::nng_stat_value(self.ptr())),
+				StatKind::Counter => StatValue::Counter(nng_sys::nng_stat_value(self.ptr())),
+				StatKind::String => {
+					StatValue::String(from_c_str(nng_sys::nng_stat_string(self.ptr())))
+				},
+				StatKind::Boolean => StatValue::Boolean(nng_sys::nng_stat_bool(self.ptr())),
+				StatKind::Id => StatValue::Id(nng_sys::nng_stat_value(self.ptr())),
+				StatKind::Scope | StatKind::Unknown(_) => StatValue::None,
+			}
+		}
+	}
+
+	/// Returns the time at which the statistic was collected.
+	///
+	/// The time is measured from an arbitrary point in the past, so it is only
+	/// useful for comparing with the timestamps of other statistics, such as
+	/// those from an earlier snapshot.
+	pub fn timestamp(self) -> Duration
+	{
+		Duration::from_millis(unsafe { nng_sys::nng_stat_timestamp(self.ptr()) })
+	}
+
+	/// Returns an iterator over the children of the statistic.
+	///
+	/// Only scopes have children.
+	pub fn children(self) -> StatChildren<'a>
+	{
+		let next = unsafe { nng_sys::nng_stat_child(self.ptr()) };
+		StatChildren { next: NonNull::new(next), _snapshot: PhantomData }
+	}
+
+	/// Returns the first statistic with the given name, searching this
+	/// statistic and its descendants depth-first.
+	pub fn find(self, name: &str) -> Option<Stat<'a>>
+	{
+		let name = CString::new(name).ok()?;
+		let ptr = unsafe { nng_sys::nng_stat_find(self.ptr(), name.as_ptr()) };
+
+		NonNull::new(ptr).map(|ptr| Stat { ptr, _snapshot: PhantomData })
+	}
+
+	/// Returns the raw pointer to the node.
+	fn ptr(self) -> *mut nng_sys::nng_stat { self.ptr.as_ptr() }
+}
+
+impl fmt::Debug for Stat<'_>
+{
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+	{
+		let mut s = f.debug_struct("Stat");
+		s.field("name", &self.name()).field("kind", &self.kind());
+
+		match self.kind() {
+			StatKind::Scope => s.field("children", &self.children().collect::<Vec<_>>()),
+			_ => s.field("value", &self.value()).field("unit", &self.unit()),
+		};
+
+		s.finish()
+	}
+}
+
+unsafe impl Send for Stat<'_> {}
+unsafe impl Sync for Stat<'_> {}
+
+/// An iterator over the children of a [`Stat`].
+///
+///
+/// [`Stat`]: struct.Stat.html
+#[derive(Clone, Debug)]
+pub struct StatChildren<'a>
+{
+	/// The next child to return.
+	next: Option<NonNull<nng_sys::nng_stat>>,
+
+	/// The snapshot that owns the nodes.
+	_snapshot: PhantomData<&'a Stats>,
+}
+
+impl<'a> Iterator for StatChildren<'a>
+{
+	type Item = Stat<'a>;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		let ptr = self.next?;
+		self.next = NonNull::new(unsafe { nng_sys::nng_stat_next(ptr.as_ptr()) });
+
+		Some(Stat { ptr, _snapshot: PhantomData })
+	}
+}
+
+impl FusedIterator for StatChildren<'_> {}
+
+unsafe impl Send for StatChildren<'_> {}
+unsafe impl Sync for StatChildren<'_> {}
+
+/// The kind of a [`Stat`].
+///
+///
+/// [`Stat`]: struct.Stat.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StatKind
+{
+	/// A group of related statistics, such as those of a single socket.
+	Scope,
+
+	/// A value that can go up and down, such as the number of open pipes.
+	Level,
+
+	/// A value that only ever increases, such as the number of sent messages.
+	Counter,
+
+	/// A string, such as the name of a socket.
+	String,
+
+	/// A boolean.
+	Boolean,
+
+	/// The numeric identifier of an object, such as a socket ID.
+	Id,
+
+	/// A kind of statistic that is not known to this crate.
+	Unknown(i32),
+}
+
+/// The unit of the value of a [`Stat`].
+///
+///
+/// [`Stat`]: struct.Stat.html
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum StatUnit
+{
+	/// The value has no unit.
+	None,
+
+	/// The value is a number of bytes.
+	Bytes,
+
+	/// The value is a number of messages.
+	Messages,
+
+	/// The value is a number of milliseconds.
+	Millis,
+
+	/// The value is a number of events.
+	Events,
+
+	/// A unit that is not known to this crate.
+	Unknown(i32),
+}
+
+/// The value of a [`Stat`].
+///
+///
+/// [`Stat`]: struct.Stat.html
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum StatValue<'a>
+{
+	/// The statistic is a scope or of an unknown kind and has no value.
+	None,
+
+	/// The value of a level.
+	Level(u64),
+
+	/// The value of a counter.
+	Counter(u64),
+
+	/// The value of a string.
+	String(Cow<'a, str>),
+
+	/// The value of a boolean.
+	Boolean(bool),
+
+	/// The value of an identifier.
+	Id(u64),
+}
+
+/// Converts a C string owned by the snapshot, treating null as empty.
+unsafe fn from_c_str<'a>(ptr: *const c_char) -> Cow<'a, str>
+{
+	if ptr.is_null() { Cow::Borrowed("") } else { CStr::from_ptr(ptr).to_string_lossy() }
+}
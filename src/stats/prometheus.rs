@@ -0,0 +1,270 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::{Stat, StatKind, StatValue, Stats};
+use crate::{
+	dialer::Dialer,
+	error::{Error, Result},
+	listener::Listener,
+	options::{Options, SocketName},
+	socket::Socket,
+};
+
+/// Renders the statistics of sockets, dialers and listeners in the Prometheus
+/// text exposition format.
+///
+/// Counters are exported as `nng_<scope>_<name>_total` and levels as gauges
+/// named `nng_<scope>_<name>`, where the scope is `socket`, `dialer` or
+/// `listener`. Every sample is labeled with the ID, [`SocketName`] and
+/// protocol of the socket it belongs to, as long as that socket has also been
+/// added to the exporter. The samples of dialers and listeners are also labeled
+/// with their own ID and URL, and with just the ID of their socket if that
+/// socket has not been added.
+///
+/// Objects that are closed after being added to the exporter are skipped.
+/// The rendered text can be served by any HTTP server.
+///
+/// ## Example
+///
+/// ```
+/// use nng::{options::{Options, SocketName}, *};
+///
+/// let socket = Socket::new(Protocol::Pair1)?;
+/// socket.set_opt::<SocketName>("front".to_string())?;
+/// let listener = Listener::new(&socket, "inproc://nng/stats/prometheus")?;
+///
+/// let mut exporter = PrometheusExporter::new();
+/// exporter.add_socket(&socket).add_listener(listener);
+///
+/// let text = exporter.render()?;
+/// assert!(text.contains("# TYPE nng_socket_tx_msgs_total counter\n"));
+/// assert!(text.contains("# TYPE nng_socket_listeners gauge\n"));
+/// assert!(text.contains(r#"socket_name="front""#));
+/// assert!(text.contains(r#"url="inproc://nng/stats/prometheus""#));
+///
+/// // Listeners are labeled with the socket that they belong to.
+/// let accepted = text.lines().find(|l| l.starts_with("nng_listener_accept_total")).unwrap();
+/// assert!(accepted.contains(r#"socket_name="front""#));
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`SocketName`]: options/struct.SocketName.html
+#[derive(Clone, Debug, Default)]
+pub struct PrometheusExporter
+{
+	/// The sockets whose statistics are exported.
+	sockets: Vec<Socket>,
+
+	/// The dialers whose statistics are exported.
+	dialers: Vec<Dialer>,
+
+	/// The listeners whose statistics are exported.
+	listeners: Vec<Listener>,
+}
+impl PrometheusExporter
+{
+	/// Creates a new exporter with no objects to export.
+	pub fn new() -> Self { PrometheusExporter::default() }
+
+	/// Adds the statistics of the socket to the exported metrics.
+	pub fn add_socket(&mut self, socket: &Socket) -> &mut Self
+	{
+		self.sockets.push(socket.clone());
+		self
+	}
+
+	/// Adds the statistics of the dialer to the exported metrics.
+	pub fn add_dialer(&mut self, dialer: Dialer) -> &mut Self
+	{
+		self.dialers.push(dialer);
+		self
+	}
+
+	/// Adds the statistics of the listener to the exported metrics.
+	pub fn add_listener(&mut self, listener: Listener) -> &mut Self
+	{
+		self.listeners.push(listener);
+		self
+	}
+
+	/// Renders the current statistics of all added objects.
+	///
+	/// # Errors
+	///
+	/// * [`NotSupported`]: NNG was built without support for statistics.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	///
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn render(&self) -> Result<String>
+	{
+		let mut families = BTreeMap::new();
+		let mut socket_labels = BTreeMap::new();
+
+		for socket in &self.sockets {
+			let Some(stats) = skip_closed(socket.stats())?
+			else {
+				continue;
+			};
+
+			let root = stats.root();
+			let id = id_of(root, "id");
+			let name = socket.get_opt::<SocketName>().unwrap_or_default();
+			let labels = vec![
+				("socket_id", id.to_string()),
+				("socket_name", name),
+				("protocol", string_of(root, "protocol")),
+			];
+
+			collect(&mut families, "nng_socket", root, &labels);
+			socket_labels.insert(id, labels);
+		}
+
+		let dialers = self.dialers.iter().map(|d| ("dialer", d.stats()));
+		let listeners = self.listeners.iter().map(|l| ("listener", l.stats()));
+		for (scope, stats) in dialers.chain(listeners) {
+			let Some(stats) = skip_closed(stats)?
+			else {
+				continue;
+			};
+
+			// Fall back to just the ID if the socket itself isn't being exported. An ID of zero
+			// means that the statistic is missing.
+			let root = stats.root();
+			let socket_id = id_of(root, "socket");
+			let mut labels = match socket_labels.get(&socket_id) {
+				Some(labels) => labels.clone(),
+				None if socket_id != 0 => vec![("socket_id", socket_id.to_string())],
+				None => Vec::new(),
+			};
+
+			labels.push((
+				if scope == "dialer" { "dialer_id" } else { "listener_id" },
+				id_of(root, "id").to_string(),
+			));
+			labels.push(("url", string_of(root, "url")));
+
+			collect(&mut families, &format!("nng_{scope}"), root, &labels);
+		}
+
+		let mut out = String::new();
+		for (name, family) in families {
+			writeln!(out, "# HELP {name} {}", escape(&family.help, false)).unwrap();
+			writeln!(out, "# TYPE {name} {}", family.kind).unwrap();
+			for (labels, value) in family.samples {
+				writeln!(out, "{name}{{{labels}}} {value}").unwrap();
+			}
+		}
+
+		Ok(out)
+	}
+}
+
+/// All of the samples of a single metric.
+struct Family
+{
+	/// The description of the metric.
+	help: String,
+
+	/// The Prometheus type of the metric.
+	kind: &'static str,
+
+	/// The rendered labels and value of each sample.
+	samples: Vec<(String, u64)>,
+}
+
+/// Turns a closed object into `None`, as it has nothing left to export.
+fn skip_closed(stats: Result<Stats>) -> Result<Option<Stats>>
+{
+	match stats {
+		Ok(s) => Ok(Some(s)),
+		Err(Error::Closed) => Ok(None),
+		Err(e) => Err(e),
+	}
+}
+
+/// Adds the values in the scope to the metric families, recursing into nested scopes.
+fn collect(
+	families: &mut BTreeMap<String, Family>,
+	prefix: &str,
+	scope: Stat,
+	labels: &[(&str, String)],
+)
+{
+	for stat in scope.children() {
+		let name = format!("{}_{}", prefix, sanitize(&stat.name()));
+		let (name, kind, value) = match stat.value() {
+			StatValue::Counter(v) => (name + "_total", "counter", v),
+			StatValue::Level(v) => (name, "gauge", v),
+			StatValue::Boolean(b) => (name, "gauge", u64::from(b)),
+			StatValue::None if stat.kind() == StatKind::Scope => {
+				collect(families, &name, stat, labels);
+				continue;
+			},
+
+			// Strings and IDs are already part of the labels.
+			_ => continue,
+		};
+
+		let labels = labels
+			.iter()
+			.map(|(k, v)| format!("{}=\"{}\"", k, escape(v, true)))
+			.collect::<Vec<_>>()
+			.join(",");
+
+		families
+			.entry(name)
+			.or_insert_with(|| Family {
+				help: stat.description().into_owned(),
+				kind,
+				samples: Vec::new(),
+			})
+			.samples
+			.push((labels, value));
+	}
+}
+
+/// Returns the value of the ID statistic with the given name in the scope.
+fn id_of(scope: Stat, name: &str) -> u64
+{
+	scope
+		.children()
+		.find(|s| s.name() == name)
+		.and_then(|s| if let StatValue::Id(id) = s.value() { Some(id) } else { None })
+		.unwrap_or(0)
+}
+
+/// Returns the value of the string statistic with the given name in the scope.
+fn string_of(scope: Stat, name: &str) -> String
+{
+	scope
+		.children()
+		.find(|s| s.name() == name)
+		.and_then(
+			|s| if let StatValue::String(v) = s.value() { Some(v.into_owned()) } else { None },
+		)
+		.unwrap_or_default()
+}
+
+/// Replaces the characters that are not allowed in metric names.
+fn sanitize(name: &str) -> String
+{
+	name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' { c } else { '_' }).collect()
+}
+
+/// Escapes a help string or, if `quotes` is set, a label value.
+fn escape(s: &str, quotes: bool) -> String
+{
+	let mut out = String::with_capacity(s.len());
+	for c in s.chars() {
+		match c {
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'"' if quotes => out.push_str("\\\""),
+			c => out.push(c),
+		}
+	}
+
+	out
+}
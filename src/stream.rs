@@ -0,0 +1,867 @@
+use std::{
+	ffi::CString,
+	io::{self, IoSlice, IoSliceMut, Read, Write},
+	ops::Range,
+	os::raw::{c_uint, c_void},
+	ptr,
+	time::Duration,
+};
+
+use nng_sys::{nng_aio, nng_iov};
+
+use crate::{
+	error::{Error, Result},
+	util::{abort_unwind, duration_to_nng, validate_ptr},
+};
+
+#[cfg(any(feature = "futures", feature = "tokio"))]
+mod adapter;
+#[cfg(any(feature = "futures", feature = "tokio"))]
+pub use self::adapter::AsyncStream;
+
+/// The most buffers that NNG accepts in a single scatter-gather operation.
+const MAX_IOV: usize = 8;
+
+/// The type of the closure called when an asynchronous operation completes.
+type DoneFn = dyn FnOnce(*mut nng_aio, Result<()>) + Send + 'static;
+
+/// Dials byte streams to a remote address.
+///
+/// Unlike a [`Dialer`], a stream dialer is not associated with a socket and
+/// the connections that it creates do not speak any of the scalability
+/// protocols. Instead, each connection is a plain [`Stream`] of bytes over one
+/// of the NNG transports, such as TCP, IPC, TLS or WebSocket. This allows
+/// talking to peers that do not use NNG at all.
+///
+/// The dialer does not connect to anything until [`dial`] or [`dial_async`] is
+/// called, so it can be configured with [`Options`] beforehand.
+///
+/// See the [NNG documentation][1] for more information.
+///
+///
+/// [1]: https://nanomsg.github.io/nng/man/v1.4.0/nng_stream_dialer.5.html
+/// [`dial`]: #method.dial
+/// [`dial_async`]: #method.dial_async
+/// [`Dialer`]: struct.Dialer.html
+/// [`Options`]: options/trait.Options.html
+/// [`Stream`]: struct.Stream.html
+#[derive(Debug)]
+pub struct StreamDialer
+{
+	/// The underlying NNG dialer.
+	handle: *mut nng_sys::nng_stream_dialer,
+}
+impl StreamDialer
+{
+	/// Creates a new dialer for the given URL.
+	///
+	/// # Errors
+	///
+	/// * [`AddressInvalid`]: An invalid _url_ was specified.
+	/// * [`NotSupported`]: The scheme of the URL is not supported.
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	///
+	///
+	/// [`AddressInvalid`]: enum.Error.html#variant.AddressInvalid
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn new(url: &str) -> Result<Self>
+	{
+		let addr = CString::new(url).map_err(|_| Error::AddressInvalid)?;
+		let mut handle = ptr::null_mut();
+		let rv = unsafe { nng_sys::nng_stream_dialer_alloc(&raw mut handle, addr.as_ptr()) };
+
+		rv2res!(rv, StreamDialer { handle })
+	}
+
+	/// Connects to the remote address, blocking until the connection is made.
+	///
+	/// # Errors
+	///
+	/// * [`AddressInvalid`]: The address could not be resolved.
+	/// * [`Closed`]: The dialer has been closed.
+	/// * [`ConnectionRefused`]: The remote peer refused the connection.
+	/// * [`ConnectionReset`]: The remote peer reset the connection.
+	/// * [`DestUnreachable`]: The remote address is not reachable.
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	/// * [`PeerAuth`]: Authentication or authorization failure.
+	/// * [`TimedOut`]: The connection was not made in time.
+	///
+	///
+	/// [`AddressInvalid`]: enum.Error.html#variant.AddressInvalid
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`ConnectionRefused`]: enum.Error.html#variant.ConnectionRefused
+	/// [`ConnectionReset`]: enum.Error.html#variant.ConnectionReset
+	/// [`DestUnreachable`]: enum.Error.html#variant.DestUnreachable
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`PeerAuth`]: enum.Error.html#variant.PeerAuth
+	/// [`TimedOut`]: enum.Error.html#variant.TimedOut
+	pub fn dial(&self) -> Result<Stream>
+	{
+		run_blocking(
+			None,
+			&[],
+			|aio| unsafe { nng_sys::nng_stream_dialer_dial(self.handle, aio) },
+			Stream::from_aio,
+		)
+	}
+
+	/// Connects to the remote address in the background.
+	///
+	/// The closure is called with the new stream, or the reason that the dial
+	/// failed, once the attempt completes. It is called on an NNG thread and
+	/// so it should not block. If the closure panics, the program will log the
+	/// panic if possible and then abort, as with [`Aio`] callbacks.
+	///
+	/// Dropping or closing the dialer aborts the attempt, in which case the
+	/// closure receives [`Canceled`].
+	///
+	/// # Errors
+	///
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	///
+	/// The closure receives the same errors as [`dial`].
+	///
+	///
+	/// [`Aio`]: struct.Aio.html
+	/// [`Canceled`]: enum.Error.html#variant.Canceled
+	/// [`dial`]: #method.dial
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn dial_async<F>(&self, callback: F) -> Result<()>
+	where
+		F: FnOnce(Result<Stream>) + Send + 'static,
+	{
+		start_async(
+			None,
+			&[],
+			|aio| unsafe { nng_sys::nng_stream_dialer_dial(self.handle, aio) },
+			|aio, res| callback(res.map(|()| Stream::from_aio(aio))),
+		)
+	}
+
+	/// Closes the dialer.
+	///
+	/// Any dial attempts that are in progress are aborted and later attempts
+	/// fail with [`Closed`]. Streams that have already been created are not
+	/// affected. The dialer is also closed when it is dropped.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	pub fn close(&self)
+	{
+		unsafe {
+			nng_sys::nng_stream_dialer_close(self.handle);
+		}
+	}
+}
+
+impl Drop for StreamDialer
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			nng_sys::nng_stream_dialer_free(self.handle);
+		}
+	}
+}
+
+unsafe impl Send for StreamDialer {}
+
+unsafe impl Sync for StreamDialer {}
+
+expose_options!{
+	StreamDialer :: handle -> *mut nng_sys::nng_stream_dialer;
+
+	GETOPT_BOOL = nng_sys::nng_stream_dialer_get_bool;
+	GETOPT_INT = nng_sys::nng_stream_dialer_get_int;
+	GETOPT_MS = nng_sys::nng_stream_dialer_get_ms;
+	GETOPT_PTR = nng_sys::nng_stream_dialer_get_ptr;
+	GETOPT_SIZE = nng_sys::nng_stream_dialer_get_size;
+	GETOPT_SOCKADDR = nng_sys::nng_stream_dialer_get_addr;
+	GETOPT_STRING = nng_sys::nng_stream_dialer_get_string;
+	GETOPT_UINT64 = nng_sys::nng_stream_dialer_get_uint64;
+
+	SETOPT = nng_sys::nng_stream_dialer_set;
+	SETOPT_BOOL = nng_sys::nng_stream_dialer_set_bool;
+	SETOPT_INT = nng_sys::nng_stream_dialer_set_int;
+	SETOPT_MS = nng_sys::nng_stream_dialer_set_ms;
+	SETOPT_PTR = nng_sys::nng_stream_dialer_set_ptr;
+	SETOPT_SIZE = nng_sys::nng_stream_dialer_set_size;
+	SETOPT_STRING = nng_sys::nng_stream_dialer_set_string;
+
+	Gets -> [transport::tcp::NoDelay,
+	         transport::tcp::KeepAlive];
+	Sets -> [transport::tcp::NoDelay,
+	         transport::tcp::KeepAlive,
+	         transport::tls::CaFile,
+	         transport::tls::CertKeyFile,
+	         transport::tls::Config];
+}
+
+/// Accepts byte streams from remote peers.
+///
+/// This is the listening side of a [`StreamDialer`]. The listener is created
+/// unbound so that it can be configured with [`Options`] first, after which
+/// [`listen`] binds it to the address.
+///
+/// See the [NNG documentation][1] for more information.
+///
+/// ## Example
+///
+/// ```
+/// use nng::{options::{transport::tcp::BoundPort, Options}, *};
+/// use std::io::{Read, Write};
+///
+/// let listener = StreamListener::new("tcp://127.0.0.1:0")?;
+/// listener.listen()?;
+///
+/// let port = listener.get_opt::<BoundPort>()?;
+/// let dialer = StreamDialer::new(&format!("tcp://127.0.0.1:{}", port))?;
+/// let mut client = dialer.dial()?;
+/// let mut server = listener.accept()?;
+///
+/// client.write_all(b"HELO legacy.example\r\n")?;
+///
+/// let mut line = [0; 21];
+/// server.read_exact(&mut line)?;
+/// assert_eq!(&line, b"HELO legacy.example\r\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+///
+///
+/// [1]: https://nanomsg.github.io/nng/man/v1.4.0/nng_stream_listener.5.html
+/// [`listen`]: #method.listen
+/// [`Options`]: options/trait.Options.html
+/// [`StreamDialer`]: struct.StreamDialer.html
+#[derive(Debug)]
+pub struct StreamListener
+{
+	/// The underlying NNG listener.
+	handle: *mut nng_sys::nng_stream_listener,
+}
+impl StreamListener
+{
+	/// Creates a new listener for the given URL.
+	///
+	/// The listener does not accept connections until [`listen`] is called.
+	///
+	/// # Errors
+	///
+	/// * [`AddressInvalid`]: An invalid _url_ was specified.
+	/// * [`NotSupported`]: The scheme of the URL is not supported.
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	///
+	///
+	/// [`AddressInvalid`]: enum.Error.html#variant.AddressInvalid
+	/// [`listen`]: #method.listen
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn new(url: &str) -> Result<Self>
+	{
+		let addr = CString::new(url).map_err(|_| Error::AddressInvalid)?;
+		let mut handle = ptr::null_mut();
+		let rv = unsafe { nng_sys::nng_stream_listener_alloc(&raw mut handle, addr.as_ptr()) };
+
+		rv2res!(rv, StreamListener { handle })
+	}
+
+	/// Binds the listener to its address.
+	///
+	/// # Errors
+	///
+	/// * [`AddressInUse`]: The address is already in use.
+	/// * [`AddressInvalid`]: The address could not be resolved.
+	/// * [`Closed`]: The listener has been closed.
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	/// * [`PermissionDenied`]: The process may not bind to the address.
+	///
+	///
+	/// [`AddressInUse`]: enum.Error.html#variant.AddressInUse
+	/// [`AddressInvalid`]: enum.Error.html#variant.AddressInvalid
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`PermissionDenied`]: enum.Error.html#variant.PermissionDenied
+	pub fn listen(&self) -> Result<()>
+	{
+		let rv = unsafe { nng_sys::nng_stream_listener_listen(self.handle) };
+		rv2res!(rv)
+	}
+
+	/// Waits for a remote peer to connect and returns the new stream.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The listener has been closed.
+	/// * [`ConnectionAborted`]: The connection was aborted before it was
+	///   accepted.
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	/// * [`PeerAuth`]: Authentication or authorization failure.
+	/// * [`State`]: The listener is not listening.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`ConnectionAborted`]: enum.Error.html#variant.ConnectionAborted
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`PeerAuth`]: enum.Error.html#variant.PeerAuth
+	/// [`State`]: enum.Error.html#variant.State
+	pub fn accept(&self) -> Result<Stream>
+	{
+		run_blocking(
+			None,
+			&[],
+			|aio| unsafe { nng_sys::nng_stream_listener_accept(self.handle, aio) },
+			Stream::from_aio,
+		)
+	}
+
+	/// Accepts a connection from a remote peer in the background.
+	///
+	/// The closure is called with the new stream, or the reason that the
+	/// operation failed, once a peer connects. It is called on an NNG thread
+	/// and so it should not block. If the closure panics, the program will log
+	/// the panic if possible and then abort, as with [`Aio`] callbacks.
+	///
+	/// Dropping or closing the listener aborts the operation, in which case
+	/// the closure receives [`Closed`].
+	///
+	/// # Errors
+	///
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	///
+	/// The closure receives the same errors as [`accept`].
+	///
+	///
+	/// [`accept`]: #method.accept
+	/// [`Aio`]: struct.Aio.html
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn accept_async<F>(&self, callback: F) -> Result<()>
+	where
+		F: FnOnce(Result<Stream>) + Send + 'static,
+	{
+		start_async(
+			None,
+			&[],
+			|aio| unsafe { nng_sys::nng_stream_listener_accept(self.handle, aio) },
+			|aio, res| callback(res.map(|()| Stream::from_aio(aio))),
+		)
+	}
+
+	/// Closes the listener.
+	///
+	/// Any accept operations that are in progress are aborted and the address
+	/// is released. Streams that have already been accepted are not affected.
+	/// The listener is also closed when it is dropped.
+	pub fn close(&self)
+	{
+		unsafe {
+			nng_sys::nng_stream_listener_close(self.handle);
+		}
+	}
+}
+
+impl Drop for StreamListener
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			nng_sys::nng_stream_listener_free(self.handle);
+		}
+	}
+}
+
+unsafe impl Send for StreamListener {}
+
+unsafe impl Sync for StreamListener {}
+
+expose_options!{
+	StreamListener :: handle -> *mut nng_sys::nng_stream_listener;
+
+	GETOPT_BOOL = nng_sys::nng_stream_listener_get_bool;
+	GETOPT_INT = nng_sys::nng_stream_listener_get_int;
+	GETOPT_MS = nng_sys::nng_stream_listener_get_ms;
+	GETOPT_PTR = nng_sys::nng_stream_listener_get_ptr;
+	GETOPT_SIZE = nng_sys::nng_stream_listener_get_size;
+	GETOPT_SOCKADDR = nng_sys::nng_stream_listener_get_addr;
+	GETOPT_STRING = nng_sys::nng_stream_listener_get_string;
+	GETOPT_UINT64 = nng_sys::nng_stream_listener_get_uint64;
+
+	SETOPT = nng_sys::nng_stream_listener_set;
+	SETOPT_BOOL = nng_sys::nng_stream_listener_set_bool;
+	SETOPT_INT = nng_sys::nng_stream_listener_set_int;
+	SETOPT_MS = nng_sys::nng_stream_listener_set_ms;
+	SETOPT_PTR = nng_sys::nng_stream_listener_set_ptr;
+	SETOPT_SIZE = nng_sys::nng_stream_listener_set_size;
+	SETOPT_STRING = nng_sys::nng_stream_listener_set_string;
+
+	Gets -> [LocalAddr,
+	         transport::tcp::NoDelay,
+	         transport::tcp::KeepAlive,
+	         transport::tcp::BoundPort];
+	Sets -> [transport::tcp::NoDelay,
+	         transport::tcp::KeepAlive,
+	         transport::tls::CaFile,
+	         transport::tls::CertKeyFile,
+	         transport::tls::Config];
+}
+
+/// A connected byte stream.
+///
+/// Streams are created by a [`StreamDialer`] or a [`StreamListener`] and carry
+/// raw bytes without any of the framing of the scalability protocols. Data can
+/// be sent and received with the scatter-gather [`send`] and [`recv`]
+/// functions, their asynchronous counterparts, or through the standard
+/// [`Read`] and [`Write`] traits, which are also implemented for `&Stream` so
+/// that a stream can be read and written from different threads at once.
+///
+/// The asynchronous functions report to a closure rather than an [`Aio`], as
+/// the results of an `Aio` only carry messages, not a byte count along with
+/// the buffers. This is the same approach as [`HttpClient::transact_async`].
+///
+/// The stream is closed when it is dropped.
+///
+///
+/// [`Aio`]: struct.Aio.html
+/// [`HttpClient::transact_async`]: struct.HttpClient.html#method.transact_async
+/// [`Read`]: https://doc.rust-lang.org/std/io/trait.Read.html
+/// [`recv`]: #method.recv
+/// [`send`]: #method.send
+/// [`StreamDialer`]: struct.StreamDialer.html
+/// [`StreamListener`]: struct.StreamListener.html
+/// [`Write`]: https://doc.rust-lang.org/std/io/trait.Write.html
+#[derive(Debug)]
+pub struct Stream
+{
+	/// The underlying NNG stream.
+	handle: *mut nng_sys::nng_stream,
+
+	/// The timeout of operations on this stream.
+	timeout: Option<Duration>,
+}
+impl Stream
+{
+	/// Sets the timeout of the operations on this stream.
+	///
+	/// A value of `None` means that the operations never time out, which is
+	/// the default.
+	pub fn set_timeout(&mut self, dur: Option<Duration>) { self.timeout = dur; }
+
+	/// Sends data from the buffers, in order, and returns the number of bytes
+	/// that were sent.
+	///
+	/// Like a write to a socket, this may send fewer bytes than are in the
+	/// buffers, in which case the remainder needs to be sent again.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The stream has been closed.
+	/// * [`ConnectionReset`]: The remote peer reset the connection.
+	/// * [`ConnectionShutdown`]: The remote peer closed the connection.
+	/// * [`InvalidInput`]: All of the buffers are empty or there are more than
+	///   eight non-empty buffers.
+	/// * [`TimedOut`]: No data was sent before the timeout.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`ConnectionReset`]: enum.Error.html#variant.ConnectionReset
+	/// [`ConnectionShutdown`]: enum.Error.html#variant.ConnectionShutdown
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	/// [`TimedOut`]: enum.Error.html#variant.TimedOut
+	#[allow(clippy::as_ptr_cast_mut)] // NNG only reads from the buffers of a send
+	pub fn send(&self, bufs: &[IoSlice<'_>]) -> Result<usize>
+	{
+		let iov = to_iov(bufs.iter().map(|b| (b.as_ptr() as *mut c_void, b.len())))?;
+		run_blocking(
+			self.timeout,
+			&iov,
+			|aio| unsafe { nng_sys::nng_stream_send(self.handle, aio) },
+			|aio| unsafe { nng_sys::nng_aio_count(aio) },
+		)
+	}
+
+	/// Receives data into the buffers, in order, and returns the number of
+	/// bytes that were received.
+	///
+	/// This returns as soon as any data is available, so the buffers may not
+	/// be filled completely.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The stream has been closed.
+	/// * [`ConnectionReset`]: The remote peer reset the connection.
+	/// * [`ConnectionShutdown`]: The remote peer closed the connection.
+	/// * [`InvalidInput`]: All of the buffers are empty or there are more than
+	///   eight non-empty buffers.
+	/// * [`TimedOut`]: No data was received before the timeout.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`ConnectionReset`]: enum.Error.html#variant.ConnectionReset
+	/// [`ConnectionShutdown`]: enum.Error.html#variant.ConnectionShutdown
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	/// [`TimedOut`]: enum.Error.html#variant.TimedOut
+	pub fn recv(&self, bufs: &mut [IoSliceMut<'_>]) -> Result<usize>
+	{
+		let iov = to_iov(bufs.iter_mut().map(|b| (b.as_mut_ptr() as *mut c_void, b.len())))?;
+		run_blocking(
+			self.timeout,
+			&iov,
+			|aio| unsafe { nng_sys::nng_stream_recv(self.handle, aio) },
+			|aio| unsafe { nng_sys::nng_aio_count(aio) },
+		)
+	}
+
+	/// Sends data from the buffers in the background.
+	///
+	/// The closure is called with the buffers and either the number of bytes
+	/// that were sent or the reason that the operation failed. It is called on
+	/// an NNG thread and so it should not block. If the closure panics, the
+	/// program will log the panic if possible and then abort, as with [`Aio`]
+	/// callbacks.
+	///
+	/// Dropping or closing the stream aborts the operation, in which case the
+	/// closure receives [`Closed`].
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: All of the buffers are empty or there are more than
+	///   eight non-empty buffers.
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	///
+	/// The closure receives the same errors as [`send`].
+	///
+	///
+	/// [`Aio`]: struct.Aio.html
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`send`]: #method.send
+	pub fn send_async<F>(&self, mut bufs: Vec<Vec<u8>>, callback: F) -> Result<()>
+	where
+		F: FnOnce(Vec<Vec<u8>>, Result<usize>) + Send + 'static,
+	{
+		// Moving the vectors into the closure doesn't move their contents, so the pointers stay
+		// valid until the closure is called.
+		let iov = to_iov(bufs.iter_mut().map(|b| (b.as_mut_ptr() as *mut c_void, b.len())))?;
+		start_async(
+			self.timeout,
+			&iov,
+			|aio| unsafe { nng_sys::nng_stream_send(self.handle, aio) },
+			move |aio, res| callback(bufs, res.map(|()| unsafe { nng_sys::nng_aio_count(aio) })),
+		)
+	}
+
+	/// Receives data into the buffers in the background.
+	///
+	/// The closure is called with the buffers and either the number of bytes
+	/// that were received or the reason that the operation failed. The data
+	/// is written to the initialized part of each buffer, up to its length. As
+	/// with [`send_async`], the closure is called on an NNG thread, should not
+	/// block and aborts the program if it panics.
+	///
+	/// Dropping or closing the stream aborts the operation, in which case the
+	/// closure receives [`Closed`].
+	///
+	/// # Errors
+	///
+	/// * [`InvalidInput`]: All of the buffers are empty or there are more than
+	///   eight non-empty buffers.
+	/// * [`OutOfMemory`]: Insufficient memory is available.
+	///
+	/// The closure receives the same errors as [`recv`].
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use nng::{options::{transport::tcp::BoundPort, Options}, *};
+	/// use std::{io::IoSlice, sync::mpsc};
+	///
+	/// let listener = StreamListener::new("tcp://127.0.0.1:0")?;
+	/// listener.listen()?;
+	/// let port = listener.get_opt::<BoundPort>()?;
+	///
+	/// let client = StreamDialer::new(&format!("tcp://127.0.0.1:{}", port))?.dial()?;
+	/// let server = listener.accept()?;
+	///
+	/// // Read a fixed-size header and the start of the payload in one operation.
+	/// let (tx, rx) = mpsc::channel();
+	/// server.recv_async(vec![vec![0; 4], vec![0; 64]], move |bufs, res| {
+	///     tx.send((bufs, res)).unwrap();
+	/// })?;
+	///
+	/// client.send(&[IoSlice::new(&[0, 0, 0, 5]), IoSlice::new(b"hello")])?;
+	///
+	/// let (bufs, res) = rx.recv().unwrap();
+	/// let n = res?;
+	/// assert!(n >= 4);
+	/// assert_eq!(bufs[0], [0, 0, 0, 5]);
+	/// assert_eq!(&bufs[1][..n - 4], &b"hello"[..n - 4]);
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`InvalidInput`]: enum.Error.html#variant.InvalidInput
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	/// [`recv`]: #method.recv
+	/// [`send_async`]: #method.send_async
+	pub fn recv_async<F>(&self, mut bufs: Vec<Vec<u8>>, callback: F) -> Result<()>
+	where
+		F: FnOnce(Vec<Vec<u8>>, Result<usize>) + Send + 'static,
+	{
+		let iov = to_iov(bufs.iter_mut().map(|b| (b.as_mut_ptr() as *mut c_void, b.len())))?;
+		start_async(
+			self.timeout,
+			&iov,
+			|aio| unsafe { nng_sys::nng_stream_recv(self.handle, aio) },
+			move |aio, res| callback(bufs, res.map(|()| unsafe { nng_sys::nng_aio_count(aio) })),
+		)
+	}
+
+	/// Closes the stream.
+	///
+	/// Any operations that are in progress are aborted and later operations
+	/// fail with [`Closed`]. The stream is also closed when it is dropped.
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	pub fn close(&self)
+	{
+		unsafe {
+			nng_sys::nng_stream_close(self.handle);
+		}
+	}
+
+	/// Takes ownership of the stream that a dial or accept operation produced.
+	fn from_aio(aio: *mut nng_aio) -> Self
+	{
+		let handle = unsafe { nng_sys::nng_aio_get_output(aio, 0) };
+		Stream { handle: handle as *mut nng_sys::nng_stream, timeout: None }
+	}
+}
+
+impl Read for &Stream
+{
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>
+	{
+		self.read_vectored(&mut [IoSliceMut::new(buf)])
+	}
+
+	fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize>
+	{
+		let range = iov_range(bufs, |b| b.len());
+		if range.start == range.end {
+			return Ok(0);
+		}
+
+		// The remote peer closing the connection is the end of the stream.
+		match self.recv(&mut bufs[range]) {
+			Err(Error::ConnectionShutdown) => Ok(0),
+			res => res.map_err(io::Error::from),
+		}
+	}
+}
+
+impl Read for Stream
+{
+	fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { (&*self).read(buf) }
+
+	fn read_vectored(&mut self, bufs: &mut [IoSliceMut<'_>]) -> io::Result<usize>
+	{
+		(&*self).read_vectored(bufs)
+	}
+}
+
+impl Write for &Stream
+{
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize>
+	{
+		self.write_vectored(&[IoSlice::new(buf)])
+	}
+
+	fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize>
+	{
+		let range = iov_range(bufs, |b| b.len());
+		if range.start == range.end {
+			return Ok(0);
+		}
+
+		self.send(&bufs[range]).map_err(io::Error::from)
+	}
+
+	fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl Write for Stream
+{
+	fn write(&mut self, buf: &[u8]) -> io::Result<usize> { (&*self).write(buf) }
+
+	fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize>
+	{
+		(&*self).write_vectored(bufs)
+	}
+
+	fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
+impl Drop for Stream
+{
+	fn drop(&mut self)
+	{
+		unsafe {
+			nng_sys::nng_stream_free(self.handle);
+		}
+	}
+}
+
+unsafe impl Send for Stream {}
+
+unsafe impl Sync for Stream {}
+
+expose_options!{
+	Stream :: handle -> *mut nng_sys::nng_stream;
+
+	GETOPT_BOOL = nng_sys::nng_stream_get_bool;
+	GETOPT_INT = nng_sys::nng_stream_get_int;
+	GETOPT_MS = nng_sys::nng_stream_get_ms;
+	GETOPT_PTR = nng_sys::nng_stream_get_ptr;
+	GETOPT_SIZE = nng_sys::nng_stream_get_size;
+	GETOPT_SOCKADDR = nng_sys::nng_stream_get_addr;
+	GETOPT_STRING = nng_sys::nng_stream_get_string;
+	GETOPT_UINT64 = nng_sys::nng_stream_get_uint64;
+
+	SETOPT = nng_sys::nng_stream_set;
+	SETOPT_BOOL = nng_sys::nng_stream_set_bool;
+	SETOPT_INT = nng_sys::nng_stream_set_int;
+	SETOPT_MS = nng_sys::nng_stream_set_ms;
+	SETOPT_PTR = nng_sys::nng_stream_set_ptr;
+	SETOPT_SIZE = nng_sys::nng_stream_set_size;
+	SETOPT_STRING = nng_sys::nng_stream_set_string;
+
+	Gets -> [LocalAddr, RemAddr,
+	         transport::tcp::NoDelay,
+	         transport::tcp::KeepAlive,
+	         transport::tls::Verified,
+	         transport::tls::PeerCn,
+	         transport::tls::PeerAltNames];
+	Sets -> [transport::tcp::NoDelay,
+	         transport::tcp::KeepAlive];
+}
+
+/// Returns the range of buffers to pass to NNG, skipping the empty ones at the start and any past
+/// the most that NNG accepts.
+///
+/// This is only meant for the `io` traits, which are allowed to transfer less than was asked.
+fn iov_range<T, L: Fn(&T) -> usize>(bufs: &[T], len: L) -> Range<usize>
+{
+	let start = bufs.iter().position(|b| len(b) != 0).unwrap_or(bufs.len());
+	start..bufs.len().min(start + MAX_IOV)
+}
+
+/// Creates the NNG description of the non-empty buffers.
+fn to_iov<I: Iterator<Item = (*mut c_void, usize)>>(bufs: I) -> Result<Vec<nng_iov>>
+{
+	let iov: Vec<_> = bufs
+		.filter(|&(_, len)| len != 0)
+		.map(|(buf, len)| nng_iov { iov_buf: buf, iov_len: len })
+		.collect();
+
+	if iov.is_empty() || iov.len() > MAX_IOV { Err(Error::InvalidInput) } else { Ok(iov) }
+}
+
+/// Runs an operation on a new AIO and waits for it to complete.
+///
+/// The `start` closure begins the operation and the `finish` closure extracts its output.
+#[allow(clippy::cast_possible_truncation)] // There are never more than eight buffers
+fn run_blocking<S, F, T>(
+	timeout: Option<Duration>,
+	iov: &[nng_iov],
+	start: S,
+	finish: F,
+) -> Result<T>
+where
+	S: FnOnce(*mut nng_aio),
+	F: FnOnce(*mut nng_aio) -> T,
+{
+	let mut aiop: *mut nng_aio = ptr::null_mut();
+	let rv = unsafe { nng_sys::nng_aio_alloc(&raw mut aiop, None, ptr::null_mut()) };
+	let aio = validate_ptr(rv, aiop)?.as_ptr();
+
+	let res = unsafe {
+		nng_sys::nng_aio_set_timeout(aio, duration_to_nng(timeout));
+		let rv = nng_sys::nng_aio_set_iov(aio, iov.len() as c_uint, iov.as_ptr());
+		rv2res!(rv).and_then(|()| {
+			start(aio);
+			nng_sys::nng_aio_wait(aio);
+
+			let rv = nng_sys::nng_aio_result(aio);
+			rv2res!(rv).map(|()| finish(aio))
+		})
+	};
+
+	unsafe { nng_sys::nng_aio_free(aio) };
+	res
+}
+
+/// Starts an operation on a new AIO and calls `done` on an NNG thread once it completes.
+///
+/// The `done` closure receives the AIO so that it can extract the output of the operation.
+#[allow(clippy::cast_possible_truncation)] // There are never more than eight buffers
+fn start_async<S, D>(timeout: Option<Duration>, iov: &[nng_iov], start: S, done: D) -> Result<()>
+where
+	S: FnOnce(*mut nng_aio),
+	D: FnOnce(*mut nng_aio, Result<()>) + Send + 'static,
+{
+	let op = Box::into_raw(Box::new(Operation { aio: ptr::null_mut(), done: Box::new(done) }));
+
+	let mut aiop: *mut nng_aio = ptr::null_mut();
+	let rv = unsafe { nng_sys::nng_aio_alloc(&raw mut aiop, Some(trampoline), op as *mut c_void) };
+	let aio = match validate_ptr(rv, aiop) {
+		Ok(a) => a.as_ptr(),
+		Err(e) => {
+			unsafe { drop(Box::from_raw(op)) };
+			return Err(e);
+		},
+	};
+
+	let rv = unsafe {
+		nng_sys::nng_aio_set_timeout(aio, duration_to_nng(timeout));
+		nng_sys::nng_aio_set_iov(aio, iov.len() as c_uint, iov.as_ptr())
+	};
+	if let Err(e) = rv2res!(rv) {
+		unsafe {
+			nng_sys::nng_aio_free(aio);
+			drop(Box::from_raw(op));
+		}
+		return Err(e);
+	}
+
+	// The operation belongs to the callback from this point on, which might run before the call
+	// to start it has returned.
+	unsafe { (*op).aio = aio };
+	start(aio);
+	Ok(())
+}
+
+/// Trampoline function for completing an asynchronous stream operation.
+extern "C" fn trampoline(arg: *mut c_void)
+{
+	abort_unwind(|| unsafe {
+		let op = Box::from_raw(arg as *mut Operation);
+		let rv = nng_sys::nng_aio_result(op.aio);
+		(op.done)(op.aio, rv2res!(rv));
+
+		// The AIO can't be freed from its own callback, so it has to be done later.
+		nng_sys::nng_aio_reap(op.aio);
+	});
+}
+
+/// The state of an asynchronous stream operation.
+struct Operation
+{
+	/// The AIO running the operation.
+	aio: *mut nng_aio,
+
+	/// The closure to call once the operation completes.
+	done: Box<DoneFn>,
+}
@@ -0,0 +1,356 @@
+use std::{
+	io,
+	pin::Pin,
+	sync::{Arc, Mutex, MutexGuard},
+	task::{Context as TaskContext, Poll, Waker},
+};
+
+use super::Stream;
+use crate::error::{Error, Result};
+
+/// The smallest buffer that is used to receive data.
+const MIN_RECV_SIZE: usize = 8 * 1024;
+
+/// An adapter that exposes a byte stream through the asynchronous I/O traits.
+///
+/// With the `futures` feature, this implements `AsyncRead` and `AsyncWrite`
+/// from `futures-io`. With the `tokio` feature, it implements the traits of the
+/// same name from `tokio`. Either way, the operations are driven by NNG AIO
+/// objects and so no particular runtime is required.
+///
+/// Data is received into an internal buffer, which is at least eight kilobytes
+/// long, and handed out over as many reads as it takes. Writes copy the data
+/// into another internal buffer and complete immediately, while the data is
+/// sent in the background. The next write waits for the previous data to be
+/// sent, as does flushing, which is also where any error in sending it is
+/// reported. Closing the adapter flushes it and then closes the stream.
+///
+/// Any timeout set on the stream applies to each of the operations.
+///
+/// ## Example
+///
+/// ```
+/// use futures::{executor::block_on, io::{AsyncReadExt, AsyncWriteExt}};
+/// use nng::{options::{transport::tcp::BoundPort, Options}, *};
+///
+/// let listener = StreamListener::new("tcp://127.0.0.1:0")?;
+/// listener.listen()?;
+/// let port = listener.get_opt::<BoundPort>()?;
+///
+/// let dialer = StreamDialer::new(&format!("tcp://127.0.0.1:{}", port))?;
+/// let mut client = AsyncStream::new(dialer.dial()?);
+/// let mut server = AsyncStream::new(listener.accept()?);
+///
+/// block_on(async {
+///     client.write_all(b"PING\r\n").await?;
+///     client.flush().await?;
+///
+///     let mut line = [0; 6];
+///     server.read_exact(&mut line).await?;
+///     assert_eq!(&line, b"PING\r\n");
+///
+///     server.close().await?;
+///     assert_eq!(client.read(&mut line).await?, 0);
+///     Ok::<(), std::io::Error>(())
+/// })?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct AsyncStream
+{
+	/// The stream on which operations are performed.
+	stream: Stream,
+
+	/// The state shared with the completion callbacks.
+	shared: Arc<Shared>,
+}
+impl AsyncStream
+{
+	/// Creates a new adapter for the stream.
+	pub fn new(stream: Stream) -> Self
+	{
+		let shared =
+			Arc::new(Shared { read: Mutex::new(Half::new()), write: Mutex::new(Half::new()) });
+
+		AsyncStream { stream, shared }
+	}
+
+	/// Returns a reference to the underlying stream.
+	///
+	/// This can be used to read and write the options of the stream. Reading
+	/// from or writing to it directly will interleave with the operations of
+	/// the adapter.
+	pub fn get_ref(&self) -> &Stream { &self.stream }
+
+	/// Copies received data into `out`, starting a receive operation if there
+	/// is none left.
+	fn poll_recv(&self, cx: &TaskContext, out: &mut [u8]) -> Poll<io::Result<usize>>
+	{
+		let mut guard = self.shared.read.lock().unwrap();
+		let read = &mut *guard;
+		let Some(buf) = &read.buf
+		else {
+			read.waker = Some(cx.waker().clone());
+			return Poll::Pending;
+		};
+
+		if read.pos < buf.len() {
+			let n = out.len().min(buf.len() - read.pos);
+			out[..n].copy_from_slice(&buf[read.pos..read.pos + n]);
+			read.pos += n;
+			return Poll::Ready(Ok(n));
+		}
+
+		if read.eof || out.is_empty() {
+			return Poll::Ready(Ok(0));
+		}
+
+		if let Some(e) = read.error.take() {
+			return Poll::Ready(Err(e.into()));
+		}
+
+		let mut buf = read.buf.take().unwrap();
+		buf.resize(out.len().max(MIN_RECV_SIZE), 0);
+		read.waker = Some(cx.waker().clone());
+		drop(guard);
+
+		// NNG may complete the operation before it returns, so the lock can't be held here.
+		let shared = Arc::clone(&self.shared);
+		let res = self.stream.recv_async(vec![buf], move |mut bufs, res| {
+			shared.complete_recv(bufs.pop().unwrap_or_default(), res);
+		});
+
+		if let Err(e) = res {
+			self.shared.complete_recv(Vec::new(), Err(e));
+		}
+
+		Poll::Pending
+	}
+
+	/// Waits for all buffered data to be sent.
+	fn poll_drain(&self, cx: &TaskContext) -> Poll<io::Result<()>>
+	{
+		let mut write = self.shared.write.lock().unwrap();
+		let Some(buf) = write.buf.take()
+		else {
+			write.waker = Some(cx.waker().clone());
+			return Poll::Pending;
+		};
+
+		if let Some(e) = write.error.take() {
+			write.buf = Some(buf);
+			return Poll::Ready(Err(e.into()));
+		}
+
+		if buf.is_empty() {
+			write.buf = Some(buf);
+			return Poll::Ready(Ok(()));
+		}
+
+		write.waker = Some(cx.waker().clone());
+		drop(write);
+
+		self.start_send(buf);
+		Poll::Pending
+	}
+
+	/// Buffers the data and starts sending it once the previous data has been
+	/// sent.
+	fn poll_send(&self, cx: &TaskContext, data: &[u8]) -> Poll<io::Result<usize>>
+	{
+		match self.poll_drain(cx) {
+			Poll::Ready(Ok(())) if !data.is_empty() => {},
+			Poll::Ready(res) => return Poll::Ready(res.map(|()| 0)),
+			Poll::Pending => return Poll::Pending,
+		}
+
+		// Nothing else starts send operations, so the buffer is still available.
+		let mut buf = self.shared.write.lock().unwrap().buf.take().unwrap();
+		buf.extend_from_slice(data);
+		self.start_send(buf);
+
+		Poll::Ready(Ok(data.len()))
+	}
+
+	/// Flushes the buffered data and then closes the stream.
+	fn poll_close_stream(&self, cx: &TaskContext) -> Poll<io::Result<()>>
+	{
+		let res = self.poll_drain(cx);
+		if matches!(res, Poll::Ready(Ok(()))) {
+			self.stream.close();
+		}
+
+		res
+	}
+
+	/// Starts sending the contents of the buffer.
+	fn start_send(&self, buf: Vec<u8>)
+	{
+		let shared = Arc::clone(&self.shared);
+		let res = self.stream.send_async(vec![buf], move |mut bufs, res| {
+			shared.complete_send(bufs.pop().unwrap_or_default(), res);
+		});
+
+		if let Err(e) = res {
+			self.shared.complete_send(Vec::new(), Err(e));
+		}
+	}
+}
+
+#[cfg(feature = "futures")]
+impl futures_io::AsyncRead for AsyncStream
+{
+	fn poll_read(
+		self: Pin<&mut Self>,
+		cx: &mut TaskContext,
+		buf: &mut [u8],
+	) -> Poll<io::Result<usize>>
+	{
+		self.poll_recv(cx, buf)
+	}
+}
+
+#[cfg(feature = "futures")]
+impl futures_io::AsyncWrite for AsyncStream
+{
+	fn poll_write(self: Pin<&mut Self>, cx: &mut TaskContext, buf: &[u8])
+	-> Poll<io::Result<usize>>
+	{
+		self.poll_send(cx, buf)
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<io::Result<()>>
+	{
+		self.poll_drain(cx)
+	}
+
+	fn poll_close(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<io::Result<()>>
+	{
+		self.poll_close_stream(cx)
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncRead for AsyncStream
+{
+	fn poll_read(
+		self: Pin<&mut Self>,
+		cx: &mut TaskContext,
+		buf: &mut tokio::io::ReadBuf,
+	) -> Poll<io::Result<()>>
+	{
+		let res = self.poll_recv(cx, buf.initialize_unfilled());
+		res.map(|r| r.map(|n| buf.advance(n)))
+	}
+}
+
+#[cfg(feature = "tokio")]
+impl tokio::io::AsyncWrite for AsyncStream
+{
+	fn poll_write(self: Pin<&mut Self>, cx: &mut TaskContext, buf: &[u8])
+	-> Poll<io::Result<usize>>
+	{
+		self.poll_send(cx, buf)
+	}
+
+	fn poll_flush(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<io::Result<()>>
+	{
+		self.poll_drain(cx)
+	}
+
+	fn poll_shutdown(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<io::Result<()>>
+	{
+		self.poll_close_stream(cx)
+	}
+}
+
+/// The state shared between the adapter and the completion callbacks.
+#[derive(Debug)]
+struct Shared
+{
+	/// The state of the receiving half.
+	read: Mutex<Half>,
+
+	/// The state of the sending half.
+	write: Mutex<Half>,
+}
+impl Shared
+{
+	/// Stores the data or error of a completed receive operation.
+	fn complete_recv(&self, mut buf: Vec<u8>, res: Result<usize>)
+	{
+		let mut read = self.read.lock().unwrap();
+		match res {
+			Ok(n) => buf.truncate(n),
+
+			// The remote peer closing the connection is the end of the stream.
+			Err(Error::ConnectionShutdown) => {
+				read.eof = true;
+				buf.clear();
+			},
+			Err(e) => {
+				read.error = Some(e);
+				buf.clear();
+			},
+		}
+
+		read.buf = Some(buf);
+		read.pos = 0;
+		Half::wake(read);
+	}
+
+	/// Removes the data that was sent by a completed send operation.
+	fn complete_send(&self, mut buf: Vec<u8>, res: Result<usize>)
+	{
+		let mut write = self.write.lock().unwrap();
+		match res {
+			Ok(n) => drop(buf.drain(..n)),
+			Err(e) => {
+				write.error = Some(e);
+				buf.clear();
+			},
+		}
+
+		write.buf = Some(buf);
+		Half::wake(write);
+	}
+}
+
+/// The state of one direction of the stream.
+#[derive(Debug)]
+struct Half
+{
+	/// The buffered data, which is `None` while an operation is using it.
+	buf: Option<Vec<u8>>,
+
+	/// The amount of received data that has already been read.
+	pos: usize,
+
+	/// The error of the last operation, which has not yet been reported.
+	error: Option<Error>,
+
+	/// Whether the remote peer has closed the connection.
+	eof: bool,
+
+	/// The task waiting for the current operation to complete.
+	waker: Option<Waker>,
+}
+impl Half
+{
+	/// Creates the state of a direction with no data.
+	fn new() -> Self
+	{
+		Half { buf: Some(Vec::new()), pos: 0, error: None, eof: false, waker: None }
+	}
+
+	/// Releases the lock and then wakes the waiting task, if there is one.
+	fn wake(mut guard: MutexGuard<Half>)
+	{
+		let waker = guard.waker.take();
+		drop(guard);
+
+		if let Some(w) = waker {
+			w.wake();
+		}
+	}
+}
@@ -0,0 +1,203 @@
+//! Declarations for the supplemental NNG APIs.
+//!
+//! The `nng-sys` crate only generates bindings for these functions when its
+//! `supplemental` feature is enabled, which requires running Bindgen at
+//! build time. The functions themselves are always part of the NNG library, so
+//! the few that this crate uses are declared here instead.
+#![allow(non_camel_case_types)]
+
+use std::os::raw::{c_char, c_int, c_void};
+
+use nng_sys::{nng_aio, nng_sockaddr, nng_url};
+
+/// An HTTP request.
+#[repr(C)]
+pub struct nng_http_req
+{
+	_unused: [u8; 0],
+}
+
+/// An HTTP response.
+#[repr(C)]
+pub struct nng_http_res
+{
+	_unused: [u8; 0],
+}
+
+/// A handler for requests on the server side.
+#[repr(C)]
+pub struct nng_http_handler
+{
+	_unused: [u8; 0],
+}
+
+/// An HTTP server instance.
+#[repr(C)]
+pub struct nng_http_server
+{
+	_unused: [u8; 0],
+}
+
+/// A client that connects to an HTTP server.
+#[repr(C)]
+pub struct nng_http_client
+{
+	_unused: [u8; 0],
+}
+
+/// A TLS configuration shared by any number of connections.
+#[repr(C)]
+pub struct nng_tls_config
+{
+	_unused: [u8; 0],
+}
+
+/// Whether a TLS configuration is for the client or server side.
+pub type nng_tls_mode = c_int;
+pub const NNG_TLS_MODE_CLIENT: nng_tls_mode = 0;
+pub const NNG_TLS_MODE_SERVER: nng_tls_mode = 1;
+
+/// How a TLS connection authenticates the peer.
+pub type nng_tls_auth_mode = c_int;
+pub const NNG_TLS_AUTH_MODE_NONE: nng_tls_auth_mode = 0;
+pub const NNG_TLS_AUTH_MODE_OPTIONAL: nng_tls_auth_mode = 1;
+pub const NNG_TLS_AUTH_MODE_REQUIRED: nng_tls_auth_mode = 2;
+
+/// A TLS protocol version, with the major and minor numbers in separate bytes.
+pub type nng_tls_version = c_int;
+pub const NNG_TLS_1_0: nng_tls_version = 0x301;
+pub const NNG_TLS_1_1: nng_tls_version = 0x302;
+pub const NNG_TLS_1_2: nng_tls_version = 0x303;
+pub const NNG_TLS_1_3: nng_tls_version = 0x304;
+
+extern "C" {
+	pub fn nng_http_req_alloc(req: *mut *mut nng_http_req, url: *const nng_url) -> c_int;
+	pub fn nng_http_req_free(req: *mut nng_http_req);
+	pub fn nng_http_req_set_method(req: *mut nng_http_req, method: *const c_char) -> c_int;
+	pub fn nng_http_req_set_uri(req: *mut nng_http_req, uri: *const c_char) -> c_int;
+	pub fn nng_http_req_set_header(
+		req: *mut nng_http_req,
+		key: *const c_char,
+		val: *const c_char,
+	) -> c_int;
+	pub fn nng_http_req_copy_data(
+		req: *mut nng_http_req,
+		data: *const c_void,
+		size: usize,
+	) -> c_int;
+	pub fn nng_http_req_get_method(req: *mut nng_http_req) -> *const c_char;
+	pub fn nng_http_req_get_version(req: *mut nng_http_req) -> *const c_char;
+	pub fn nng_http_req_get_uri(req: *mut nng_http_req) -> *const c_char;
+	pub fn nng_http_req_get_header(req: *mut nng_http_req, key: *const c_char) -> *const c_char;
+	pub fn nng_http_req_get_data(req: *mut nng_http_req, data: *mut *mut c_void, size: *mut usize);
+
+	pub fn nng_http_res_alloc(res: *mut *mut nng_http_res) -> c_int;
+	pub fn nng_http_res_alloc_error(res: *mut *mut nng_http_res, status: u16) -> c_int;
+	pub fn nng_http_res_free(res: *mut nng_http_res);
+	pub fn nng_http_res_get_status(res: *mut nng_http_res) -> u16;
+	pub fn nng_http_res_set_status(res: *mut nng_http_res, status: u16) -> c_int;
+	pub fn nng_http_res_get_reason(res: *mut nng_http_res) -> *const c_char;
+	pub fn nng_http_res_set_reason(res: *mut nng_http_res, reason: *const c_char) -> c_int;
+	pub fn nng_http_res_get_header(res: *mut nng_http_res, key: *const c_char) -> *const c_char;
+	pub fn nng_http_res_set_header(
+		res: *mut nng_http_res,
+		key: *const c_char,
+		val: *const c_char,
+	) -> c_int;
+	pub fn nng_http_res_get_data(res: *mut nng_http_res, data: *mut *mut c_void, size: *mut usize);
+	pub fn nng_http_res_copy_data(
+		res: *mut nng_http_res,
+		data: *const c_void,
+		size: usize,
+	) -> c_int;
+
+	pub fn nng_http_handler_alloc(
+		h: *mut *mut nng_http_handler,
+		path: *const c_char,
+		cb: extern "C" fn(*mut nng_aio),
+	) -> c_int;
+	pub fn nng_http_handler_alloc_file(
+		h: *mut *mut nng_http_handler,
+		path: *const c_char,
+		file: *const c_char,
+	) -> c_int;
+	pub fn nng_http_handler_alloc_static(
+		h: *mut *mut nng_http_handler,
+		path: *const c_char,
+		data: *const c_void,
+		size: usize,
+		content_type: *const c_char,
+	) -> c_int;
+	pub fn nng_http_handler_alloc_redirect(
+		h: *mut *mut nng_http_handler,
+		path: *const c_char,
+		status: u16,
+		location: *const c_char,
+	) -> c_int;
+	pub fn nng_http_handler_alloc_directory(
+		h: *mut *mut nng_http_handler,
+		path: *const c_char,
+		dir: *const c_char,
+	) -> c_int;
+	pub fn nng_http_handler_free(h: *mut nng_http_handler);
+	pub fn nng_http_handler_set_method(h: *mut nng_http_handler, method: *const c_char) -> c_int;
+	pub fn nng_http_handler_set_host(h: *mut nng_http_handler, host: *const c_char) -> c_int;
+	pub fn nng_http_handler_set_tree(h: *mut nng_http_handler) -> c_int;
+	pub fn nng_http_handler_set_data(
+		h: *mut nng_http_handler,
+		data: *mut c_void,
+		dtor: Option<extern "C" fn(*mut c_void)>,
+	) -> c_int;
+	pub fn nng_http_handler_get_data(h: *mut nng_http_handler) -> *mut c_void;
+
+	pub fn nng_http_server_hold(server: *mut *mut nng_http_server, url: *const nng_url) -> c_int;
+	pub fn nng_http_server_release(server: *mut nng_http_server);
+	pub fn nng_http_server_start(server: *mut nng_http_server) -> c_int;
+	pub fn nng_http_server_stop(server: *mut nng_http_server);
+	pub fn nng_http_server_add_handler(
+		server: *mut nng_http_server,
+		h: *mut nng_http_handler,
+	) -> c_int;
+	pub fn nng_http_server_get_addr(server: *mut nng_http_server, addr: *mut nng_sockaddr)
+	-> c_int;
+	pub fn nng_http_server_set_tls(server: *mut nng_http_server, cfg: *mut nng_tls_config)
+	-> c_int;
+
+	pub fn nng_http_client_alloc(client: *mut *mut nng_http_client, url: *const nng_url) -> c_int;
+	pub fn nng_http_client_free(client: *mut nng_http_client);
+	pub fn nng_http_client_transact(
+		client: *mut nng_http_client,
+		req: *mut nng_http_req,
+		res: *mut nng_http_res,
+		aio: *mut nng_aio,
+	);
+	pub fn nng_http_client_set_tls(client: *mut nng_http_client, cfg: *mut nng_tls_config)
+	-> c_int;
+
+	pub fn nng_tls_config_alloc(cfg: *mut *mut nng_tls_config, mode: nng_tls_mode) -> c_int;
+	pub fn nng_tls_config_free(cfg: *mut nng_tls_config);
+	pub fn nng_tls_config_server_name(cfg: *mut nng_tls_config, name: *const c_char) -> c_int;
+	pub fn nng_tls_config_ca_chain(
+		cfg: *mut nng_tls_config,
+		chain: *const c_char,
+		crl: *const c_char,
+	) -> c_int;
+	pub fn nng_tls_config_own_cert(
+		cfg: *mut nng_tls_config,
+		cert: *const c_char,
+		key: *const c_char,
+		pass: *const c_char,
+	) -> c_int;
+	pub fn nng_tls_config_auth_mode(cfg: *mut nng_tls_config, mode: nng_tls_auth_mode) -> c_int;
+	pub fn nng_tls_config_ca_file(cfg: *mut nng_tls_config, path: *const c_char) -> c_int;
+	pub fn nng_tls_config_cert_key_file(
+		cfg: *mut nng_tls_config,
+		path: *const c_char,
+		pass: *const c_char,
+	) -> c_int;
+	pub fn nng_tls_config_version(
+		cfg: *mut nng_tls_config,
+		min: nng_tls_version,
+		max: nng_tls_version,
+	) -> c_int;
+}
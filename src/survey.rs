@@ -0,0 +1,334 @@
+use std::iter::FusedIterator;
+
+#[cfg(feature = "futures")]
+use std::{
+	future::Future,
+	pin::Pin,
+	task::{Context as TaskContext, Poll},
+};
+
+#[cfg(feature = "futures")]
+use futures_core::stream::{FusedStream, Stream};
+
+use crate::{
+	ctx::Context,
+	error::{Error, Result},
+	future,
+	message::Message,
+	pipe::Pipe,
+	protocol::Protocol,
+	socket::Socket,
+};
+
+#[cfg(feature = "futures")]
+use crate::future::{RecvFuture, SendFuture};
+
+/// A surveyor that collects the responses to each survey.
+///
+/// Every survey is run on a separate [`Context`], which means that multiple
+/// surveys can be active on the same socket at once and that the responses to
+/// one survey are never mixed up with those of another. Each context takes the
+/// value of the [`SurveyTime`] option that is set on the socket at the moment
+/// the survey starts, which determines how long responses are collected.
+///
+/// ## Example
+///
+/// ```
+/// use std::{thread, time::Duration};
+/// use nng::{options::{protocol::survey::SurveyTime, Options}, *};
+///
+/// let socket = Socket::new(Protocol::Surveyor0)?;
+/// socket.set_opt::<SurveyTime>(Some(Duration::from_millis(100)))?;
+/// socket.listen("inproc://nng/survey/example")?;
+///
+/// let respondent = Socket::new(Protocol::Respondent0)?;
+/// respondent.dial("inproc://nng/survey/example")?;
+/// thread::spawn(move || {
+///     let msg = respondent.recv().unwrap();
+///     respondent.send(msg).unwrap();
+/// });
+///
+/// let surveyor = Surveyor::new(socket)?;
+/// let responses = surveyor.survey("ping".as_bytes())?.collect::<Result<Vec<_>>>()?;
+///
+/// assert_eq!(responses.len(), 1);
+/// assert_eq!(&responses[0].0[..], b"ping");
+/// # Ok::<(), nng::Error>(())
+/// ```
+///
+///
+/// [`Context`]: struct.Context.html
+/// [`SurveyTime`]: options/protocol/survey/struct.SurveyTime.html
+#[derive(Clone, Debug)]
+pub struct Surveyor
+{
+	/// The socket on which surveys are made.
+	socket: Socket,
+}
+impl Surveyor
+{
+	/// Creates a new surveyor that runs surveys on the given socket.
+	///
+	/// # Errors
+	///
+	/// * [`NotSupported`]: The socket was not opened with [`Protocol::Surveyor0`].
+	///
+	///
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`Protocol::Surveyor0`]: enum.Protocol.html#variant.Surveyor0
+	pub fn new(socket: Socket) -> Result<Self>
+	{
+		if socket.protocol() != Protocol::Surveyor0 {
+			return Err(Error::NotSupported);
+		}
+
+		Ok(Surveyor { socket })
+	}
+
+	/// Sends out a survey and returns an iterator over the responses.
+	///
+	/// The iterator blocks while waiting for each response and ends once the
+	/// survey has expired, including when it is first read after that point.
+	/// Each response is paired with the pipe on which it arrived, if known. Any
+	/// responses that have not been taken out of the iterator by the time the
+	/// survey expires are discarded, so concurrent surveys should be collected
+	/// concurrently. If receiving a response fails
+	/// for any other reason, such as the socket being closed, the iterator
+	/// yields the error and then ends.
+	///
+	/// # Errors
+	///
+	/// * [`Closed`]: The socket is not open.
+	/// * [`NotSupported`]: The socket was opened in raw mode.
+	/// * [`OutOfMemory`]: Insufficient memory available.
+	///
+	/// ## Example
+	///
+	/// ```
+	/// use std::{thread, time::Duration};
+	/// use nng::{options::{protocol::survey::SurveyTime, Options}, *};
+	///
+	/// let socket = Socket::new(Protocol::Surveyor0)?;
+	/// socket.set_opt::<SurveyTime>(Some(Duration::from_millis(50)))?;
+	/// socket.listen("inproc://nng/survey/expired")?;
+	///
+	/// let surveyor = Surveyor::new(socket)?;
+	/// let responses = surveyor.survey("ping".as_bytes())?;
+	///
+	/// // Reading the responses after the survey has expired ends them without
+	/// // an error.
+	/// thread::sleep(Duration::from_millis(200));
+	/// assert!(responses.collect::<Result<Vec<_>>>()?.is_empty());
+	/// # Ok::<(), nng::Error>(())
+	/// ```
+	///
+	///
+	/// [`Closed`]: enum.Error.html#variant.Closed
+	/// [`NotSupported`]: enum.Error.html#variant.NotSupported
+	/// [`OutOfMemory`]: enum.Error.html#variant.OutOfMemory
+	pub fn survey<M: Into<Message>>(&self, msg: M) -> Result<Responses>
+	{
+		let ctx = Context::new(&self.socket)?;
+		future::block_on(ctx.send_fut(msg))?;
+
+		Ok(Responses { ctx, done: false })
+	}
+
+	/// Returns a future that sends out a survey and then resolves to a stream
+	/// of the responses.
+	///
+	/// The survey is not sent until the future is first polled. The stream
+	/// ends once the survey has expired and otherwise yields the same errors as
+	/// the iterator returned by [`Surveyor::survey`].
+	///
+	/// This function is only available when the `futures` feature is enabled.
+	///
+	/// # Errors
+	///
+	/// The future resolves to an error in the same cases as
+	/// [`Surveyor::survey`].
+	///
+	///
+	/// [`Surveyor::survey`]: struct.Surveyor.html#method.survey
+	#[cfg(feature = "futures")]
+	pub fn survey_async<M: Into<Message>>(&self, msg: M) -> SurveyFuture
+	{
+		SurveyFuture { state: SurveyState::Start(self.socket.clone(), msg.into()) }
+	}
+
+	/// Returns the socket on which surveys are made.
+	pub fn socket(&self) -> &Socket { &self.socket }
+}
+
+/// An iterator over the responses to a survey.
+///
+/// This is created by [`Surveyor::survey`].
+///
+///
+/// [`Surveyor::survey`]: struct.Surveyor.html#method.survey
+#[derive(Debug)]
+pub struct Responses
+{
+	/// The context on which the survey is running.
+	ctx: Context,
+
+	/// Whether or not the survey has ended.
+	done: bool,
+}
+
+impl Iterator for Responses
+{
+	type Item = Result<(Message, Option<Pipe>)>;
+
+	fn next(&mut self) -> Option<Self::Item>
+	{
+		if self.done {
+			return None;
+		}
+
+		let res = future::block_on(self.ctx.recv_fut());
+		self.done = res.is_err();
+		response(res)
+	}
+}
+
+impl FusedIterator for Responses {}
+
+/// Converts the result of receiving on a survey context into the next item.
+///
+/// A receive that is waiting when the survey expires fails with `TimedOut`,
+/// while one that starts after the survey has expired fails with
+/// `IncorrectState`. Either ends the responses without an error. Any other
+/// error is passed on.
+fn response(res: Result<Message>) -> Option<Result<(Message, Option<Pipe>)>>
+{
+	match res {
+		Ok(mut msg) => {
+			let pipe = msg.pipe();
+			Some(Ok((msg, pipe)))
+		},
+		Err(Error::TimedOut | Error::IncorrectState) => None,
+		Err(e) => Some(Err(e)),
+	}
+}
+
+/// A future that sends out a survey and resolves to a stream of responses.
+///
+/// This is created by [`Surveyor::survey_async`].
+///
+///
+/// [`Surveyor::survey_async`]: struct.Surveyor.html#method.survey_async
+#[cfg(feature = "futures")]
+#[derive(Debug)]
+#[must_use = "the survey is not sent until the future is polled"]
+pub struct SurveyFuture
+{
+	/// The current step of sending the survey.
+	state: SurveyState,
+}
+
+#[cfg(feature = "futures")]
+impl Future for SurveyFuture
+{
+	type Output = Result<ResponseStream>;
+
+	fn poll(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Self::Output>
+	{
+		let this = self.get_mut();
+		if let SurveyState::Start(socket, msg) =
+			std::mem::replace(&mut this.state, SurveyState::Done)
+		{
+			match Context::new(&socket) {
+				Ok(ctx) => {
+					let fut = ctx.send_fut(msg);
+					this.state = SurveyState::Sending(ctx, fut);
+				},
+				Err(e) => return Poll::Ready(Err(e)),
+			}
+		}
+
+		match std::mem::replace(&mut this.state, SurveyState::Done) {
+			SurveyState::Sending(ctx, mut fut) => match Pin::new(&mut fut).poll(cx) {
+				Poll::Ready(Ok(())) => Poll::Ready(Ok(ResponseStream::new(ctx))),
+				Poll::Ready(Err((_, e))) => Poll::Ready(Err(e)),
+				Poll::Pending => {
+					this.state = SurveyState::Sending(ctx, fut);
+					Poll::Pending
+				},
+			},
+			_ => panic!("SurveyFuture polled after completion"),
+		}
+	}
+}
+
+/// The steps of sending out a survey.
+#[cfg(feature = "futures")]
+#[derive(Debug)]
+enum SurveyState
+{
+	/// The survey has not yet been sent.
+	Start(Socket, Message),
+
+	/// The survey is being sent on the context.
+	Sending(Context, SendFuture),
+
+	/// The survey has been sent.
+	Done,
+}
+
+/// A stream of the responses to a survey.
+///
+/// This is created by [`Surveyor::survey_async`] and is only available when
+/// the `futures` feature is enabled.
+///
+///
+/// [`Surveyor::survey_async`]: struct.Surveyor.html#method.survey_async
+#[cfg(feature = "futures")]
+#[derive(Debug)]
+pub struct ResponseStream
+{
+	/// The context on which the survey is running.
+	ctx: Context,
+
+	/// The receive operation for the next response, if the survey has not
+	/// ended.
+	recv: Option<RecvFuture>,
+}
+
+#[cfg(feature = "futures")]
+impl ResponseStream
+{
+	/// Creates a new stream of responses to the survey running on the context.
+	fn new(ctx: Context) -> Self
+	{
+		let recv = Some(ctx.recv_fut());
+		ResponseStream { ctx, recv }
+	}
+}
+
+#[cfg(feature = "futures")]
+impl Stream for ResponseStream
+{
+	type Item = Result<(Message, Option<Pipe>)>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut TaskContext) -> Poll<Option<Self::Item>>
+	{
+		let this = self.get_mut();
+		let res = match &mut this.recv {
+			Some(fut) => match Pin::new(fut).poll(cx) {
+				Poll::Ready(res) => res,
+				Poll::Pending => return Poll::Pending,
+			},
+			None => return Poll::Ready(None),
+		};
+
+		this.recv = if res.is_ok() { Some(this.ctx.recv_fut()) } else { None };
+		Poll::Ready(response(res))
+	}
+}
+
+#[cfg(feature = "futures")]
+impl FusedStream for ResponseStream
+{
+	fn is_terminated(&self) -> bool { self.recv.is_none() }
+}
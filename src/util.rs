@@ -96,6 +96,7 @@ macro_rules! expose_options
 		GETOPT_BOOL = $go_b:path;
 		GETOPT_INT = $go_i:path;
 		GETOPT_MS = $go_ms:path;
+		GETOPT_PTR = $go_ptr:path;
 		GETOPT_SIZE = $go_sz:path;
 		GETOPT_SOCKADDR = $go_sa:path;
 		GETOPT_STRING = $go_str:path;
@@ -120,6 +121,7 @@ macro_rules! expose_options
 			const GETOPT_BOOL: unsafe extern "C" fn(Self::Handle, *const std::os::raw::c_char, *mut bool) -> std::os::raw::c_int = $go_b;
 			const GETOPT_INT: unsafe extern "C" fn(Self::Handle, *const std::os::raw::c_char, *mut std::os::raw::c_int) -> std::os::raw::c_int = $go_i;
 			const GETOPT_MS: unsafe extern "C" fn(Self::Handle, *const std::os::raw::c_char, *mut nng_sys::nng_duration) -> std::os::raw::c_int = $go_ms;
+			const GETOPT_PTR: unsafe extern "C" fn(Self::Handle, *const std::os::raw::c_char, *mut *mut std::os::raw::c_void) -> std::os::raw::c_int = $go_ptr;
 			const GETOPT_SIZE: unsafe extern "C" fn(Self::Handle, *const std::os::raw::c_char, *mut usize) -> std::os::raw::c_int = $go_sz;
 			const GETOPT_SOCKADDR: unsafe extern "C" fn(Self::Handle, *const std::os::raw::c_char, *mut nng_sys::nng_sockaddr) -> std::os::raw::c_int = $go_sa;
 			const GETOPT_STRING: unsafe extern "C" fn(Self::Handle, *const std::os::raw::c_char, *mut *mut std::os::raw::c_char) -> std::os::raw::c_int = $go_str;